@@ -0,0 +1,103 @@
+//! Counts heap allocations made while converting records with many labels.
+//!
+//! Run with `cargo bench --bench convert_allocations`. The title map is sized up front,
+//! so it should allocate once per record instead of once per growth step.
+
+use invenio_vocb_converter::vocab::affiliations::{self, AffiliationItem, ConvertOptions, Label};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const RECORDS: usize = 10_000;
+const LABELS: usize = 12;
+
+fn labels() -> Vec<(String, String)> {
+    (0..LABELS).map(|i| (format!("l{}", i), format!("Label {}", i))).collect()
+}
+
+/// Allocations made by filling a title map the way the converter used to: unsized.
+fn growing_map_allocations(labels: &[(String, String)]) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..RECORDS {
+        let mut title = HashMap::new();
+        for (lang, label) in labels {
+            title.insert(lang.as_str(), label.as_str());
+        }
+        black_box(&title);
+    }
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Allocations made by filling a title map pre-sized to the label count.
+fn presized_map_allocations(labels: &[(String, String)]) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..RECORDS {
+        let mut title = HashMap::with_capacity(labels.len());
+        for (lang, label) in labels {
+            title.insert(lang.as_str(), label.as_str());
+        }
+        black_box(&title);
+    }
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn main() {
+    let labels = labels();
+    let growing = growing_map_allocations(&labels);
+    let presized = presized_map_allocations(&labels);
+    println!(
+        "title map allocations per record: growing {:.2}, pre-sized {:.2}",
+        growing as f64 / RECORDS as f64,
+        presized as f64 / RECORDS as f64
+    );
+
+    let item = AffiliationItem {
+        id: "https://ror.org/00aaa1234".to_string(),
+        name: "Test University".to_string(),
+        labels: labels
+            .iter()
+            .map(|(lang, label)| Label { iso639: lang.clone(), label: label.clone(), preferred: false })
+            .collect(),
+        acronyms: vec!["TU".to_string()],
+        ..Default::default()
+    };
+    let options = ConvertOptions::default();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
+    for _ in 0..RECORDS {
+        black_box(affiliations::convert_item(black_box(&item), &options));
+    }
+    let elapsed = started.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "convert_item: {:.2} allocations per record, {:?} per record",
+        allocations as f64 / RECORDS as f64,
+        elapsed / RECORDS as u32
+    );
+}
@@ -1,138 +1,4402 @@
 // Re-export the vocab module to make it available to tests
 pub mod vocab {
+    use std::fmt;
+    use std::str::FromStr;
+
+    /// The controlled vocabularies supported by the converter.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum VocabType {
+        Affiliations,
+        Names,
+        Funding,
+        Awards,
+        Subjects,
+    }
+
+    impl VocabType {
+        /// The command-line name of the vocabulary type.
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                VocabType::Affiliations => "affiliations",
+                VocabType::Names => "names",
+                VocabType::Funding => "funding",
+                VocabType::Awards => "awards",
+                VocabType::Subjects => "subjects",
+            }
+        }
+
+        /// The persistent identifier type InvenioRDM registers the vocabulary under.
+        pub fn pid_type(&self) -> &'static str {
+            match self {
+                VocabType::Affiliations => "aff",
+                VocabType::Names => "names",
+                VocabType::Funding => "fun",
+                VocabType::Awards => "awa",
+                VocabType::Subjects => "sub",
+            }
+        }
+
+        /// The identifier scheme emitted when `--id-scheme` is not given.
+        pub fn default_scheme(&self) -> &'static str {
+            match self {
+                VocabType::Affiliations => "ror",
+                VocabType::Names => "orcid",
+                VocabType::Funding => "fundref",
+                VocabType::Awards => "other",
+                VocabType::Subjects => "mesh",
+            }
+        }
+    }
+
+    impl FromStr for VocabType {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_lowercase().as_str() {
+                "affiliations" => Ok(VocabType::Affiliations),
+                "names" => Ok(VocabType::Names),
+                "funding" => Ok(VocabType::Funding),
+                "awards" => Ok(VocabType::Awards),
+                "subjects" => Ok(VocabType::Subjects),
+                other => Err(format!("Unknown vocabulary type: {}", other)),
+            }
+        }
+    }
+
+    impl fmt::Display for VocabType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
+    /// Input that could not be parsed into records.
+    #[derive(Debug)]
+    pub struct ParseError(pub String);
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    /// Records or output that failed a validation check.
+    #[derive(Debug)]
+    pub struct ValidationError(pub String);
+
+    impl fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for ValidationError {}
+
+    /// A vocabulary type or input the converter recognizes but cannot convert yet.
+    #[derive(Debug)]
+    pub struct UnsupportedError(pub String);
+
+    impl fmt::Display for UnsupportedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for UnsupportedError {}
+
+    /// How much diagnostic output to print to stderr.
+    ///
+    /// Levels are ordered: each level prints everything the previous one does.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum Verbosity {
+        /// Errors only (`-q`).
+        Quiet,
+        /// Warnings and the conversion summary.
+        #[default]
+        Normal,
+        /// Per-record diagnostics such as skipped records (`-v`).
+        Verbose,
+        /// Detailed transformation output (`-vv`).
+        Debug,
+    }
+
+    /// The category of a non-fatal problem noticed during conversion.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum WarningKind {
+        /// A record was dropped by a record-level policy.
+        SkippedRecord,
+        /// A label carries a language code that is not two or three lowercase ASCII letters.
+        InvalidLanguage,
+        /// A record's id repeats the id of an earlier record.
+        DuplicateId,
+        /// A country code has no entry in the built-in ISO 3166 table.
+        UnknownCountry,
+        /// An acronym exceeded the configured maximum length and was dropped.
+        LongAcronym,
+        /// A homepage link is not a well-formed http(s) URL and was not emitted.
+        InvalidUrl,
+        /// An entry's identifier value is already used by an earlier entry.
+        DuplicateIdentifier,
+        /// An emitted id is not known to the registry it was compared with.
+        UnknownRegistryId,
+    }
+
+    /// A non-fatal problem noticed during conversion, kept for callers to inspect.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Warning {
+        /// What kind of problem this is.
+        pub kind: WarningKind,
+        /// Zero-based index of the offending input record.
+        pub record: usize,
+        /// The offending record's id as it appeared in the input.
+        pub id: String,
+        /// Human-readable description, as printed by the CLI.
+        pub message: String,
+    }
+
+    impl fmt::Display for Warning {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    /// Counts and timing describing a finished conversion.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct ConversionReport {
+        /// Records read from the input.
+        pub records_total: usize,
+        /// Entries written to the output.
+        pub emitted_total: usize,
+        /// Records dropped during conversion.
+        pub skipped_total: usize,
+        /// Wall-clock time spent converting.
+        pub duration: std::time::Duration,
+        /// Non-fatal problems noticed while converting, in input order.
+        pub warnings: Vec<Warning>,
+    }
+
+    impl ConversionReport {
+        /// Render the report as metrics in the Prometheus text exposition format.
+        pub fn to_prometheus(&self, vocab_type: VocabType) -> String {
+            let mut out = String::new();
+            let metrics: [(&str, &str, &str, String); 4] = [
+                ("records_total", "counter", "Records read from the input.", self.records_total.to_string()),
+                ("emitted_total", "counter", "Entries written to the output.", self.emitted_total.to_string()),
+                ("skipped_total", "counter", "Records dropped during conversion.", self.skipped_total.to_string()),
+                ("duration_seconds", "gauge", "Time spent converting.", self.duration.as_secs_f64().to_string()),
+            ];
+            for (name, kind, help, value) in metrics {
+                out.push_str(&format!("# HELP invenio_vocab_{} {}\n", name, help));
+                out.push_str(&format!("# TYPE invenio_vocab_{} {}\n", name, kind));
+                out.push_str(&format!("invenio_vocab_{}{{vocab=\"{}\"}} {}\n", name, vocab_type, value));
+            }
+            out
+        }
+
+        /// Write the report as a Prometheus textfile, replacing `path` atomically.
+        pub fn write_metrics(&self, path: &std::path::Path, vocab_type: VocabType) -> std::io::Result<()> {
+            let mut tmp = path.as_os_str().to_owned();
+            tmp.push(".tmp");
+            std::fs::write(&tmp, self.to_prometheus(vocab_type))?;
+            std::fs::rename(&tmp, path)
+        }
+    }
+
+    /// JSON Schema describing the input records accepted for `vocab_type`.
+    ///
+    /// Returns `None` for vocabulary types that have no converter yet.
+    pub fn input_schema(vocab_type: VocabType) -> Option<serde_json::Value> {
+        let mut schema = match vocab_type {
+            VocabType::Affiliations => schemars::schema_for!(Vec<affiliations::AffiliationItem>),
+            VocabType::Names => schemars::schema_for!(Vec<names::NameItem>),
+            VocabType::Subjects => schemars::schema_for!(Vec<subjects::SubjectItem>),
+            VocabType::Awards => schemars::schema_for!(Vec<awards::AwardItem>),
+            VocabType::Funding => return None,
+        };
+        schema.insert("title".to_string(), format!("{} input", vocab_type).into());
+        Some(schema.to_value())
+    }
+
+    /// Compute a stable FNV-1a hash of the file at `path`, as a hex string.
+    pub fn file_hash(path: &std::path::Path) -> std::io::Result<String> {
+        use std::io::Read;
+
+        let mut file = std::fs::File::open(path)?;
+        let mut buffer = [0u8; 64 * 1024];
+        let mut hash = FNV_OFFSET_BASIS;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hash = fnv1a(hash, &buffer[..read]);
+        }
+        Ok(format!("{:016x}", hash))
+    }
+
+    /// Initial state of an FNV-1a hash.
+    pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+    /// Feed `bytes` into the FNV-1a hash state `hash`.
+    pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    /// Path of the state file recording the input hash of the run that produced `output_path`.
+    pub fn state_path(output_path: &std::path::Path) -> std::path::PathBuf {
+        let mut name = output_path.as_os_str().to_owned();
+        name.push(".input-hash");
+        std::path::PathBuf::from(name)
+    }
+
+    /// Whether `output_path` exists and was produced from an input with hash `input_hash`.
+    pub fn is_unchanged(output_path: &std::path::Path, input_hash: &str) -> bool {
+        output_path.exists()
+            && std::fs::read_to_string(state_path(output_path))
+                .map(|recorded| recorded.trim() == input_hash)
+                .unwrap_or(false)
+    }
+
+    /// A record that failed validation, as reported by `validate_stream`.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct InvalidRecord {
+        /// Zero-based index of the record in the input array, or of the record being read
+        /// when the document itself is malformed.
+        pub record: usize,
+        /// Why the record was rejected.
+        pub message: String,
+    }
+
+    impl fmt::Display for InvalidRecord {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "record {}: {}", self.record, self.message)
+        }
+    }
+
+    /// Check that `reader` holds a JSON array whose elements deserialize as `T`.
+    ///
+    /// The array is streamed one element at a time, so memory stays bounded by the largest
+    /// record rather than the whole file. Validation stops after `max_errors` invalid records;
+    /// a syntax error ends it immediately and is reported as the last entry.
+    pub fn validate_stream<T, R>(reader: R, max_errors: usize) -> Vec<InvalidRecord>
+    where
+        T: serde::de::DeserializeOwned,
+        R: std::io::Read,
+    {
+        use serde::de::{Deserializer as _, SeqAccess, Visitor};
+
+        struct ValidatingVisitor<'a, T> {
+            errors: &'a mut Vec<InvalidRecord>,
+            seen: &'a mut usize,
+            max_errors: usize,
+            marker: std::marker::PhantomData<T>,
+        }
+
+        impl<'de, T: serde::de::DeserializeOwned> Visitor<'de> for ValidatingVisitor<'_, T> {
+            type Value = ();
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an array of records")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+                while let Some(value) = seq.next_element::<serde_json::Value>()? {
+                    if let Err(err) = serde_json::from_value::<T>(value) {
+                        self.errors.push(InvalidRecord { record: *self.seen, message: err.to_string() });
+                    }
+                    *self.seen += 1;
+                    if self.errors.len() >= self.max_errors {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        let mut errors = Vec::new();
+        let mut seen = 0;
+        let max_errors = max_errors.max(1);
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let result = (&mut deserializer).deserialize_seq(ValidatingVisitor::<T> {
+            errors: &mut errors,
+            seen: &mut seen,
+            max_errors,
+            marker: std::marker::PhantomData,
+        });
+        // Stopping early leaves the array unterminated, which is not a problem with the input.
+        if errors.len() >= max_errors {
+            return errors;
+        }
+        if let Err(err) = result.and_then(|()| deserializer.end()) {
+            errors.push(InvalidRecord { record: seen, message: err.to_string() });
+        }
+        errors
+    }
+
+    /// Call `on_record` with each element of the JSON array in `reader`, deserialized as `T`.
+    ///
+    /// Like `validate_stream`, the array is streamed one element at a time. Trailing data after
+    /// the array is a parse error unless `lenient` is set.
+    pub(crate) fn stream_records<T, R>(
+        reader: R,
+        lenient: bool,
+        on_record: &mut dyn FnMut(T) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        T: serde::de::DeserializeOwned,
+        R: std::io::Read,
+    {
+        use serde::de::{Deserializer as _, Error as _, SeqAccess, Visitor};
+
+        struct StreamingVisitor<'a, T> {
+            on_record: &'a mut dyn FnMut(T) -> Result<(), Box<dyn std::error::Error>>,
+            // An error from `on_record`, kept so it reaches the caller with its own type.
+            failure: &'a mut Option<Box<dyn std::error::Error>>,
+        }
+
+        impl<'de, T: serde::de::DeserializeOwned> Visitor<'de> for StreamingVisitor<'_, T> {
+            type Value = ();
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an array of records")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+                while let Some(record) = seq.next_element::<T>()? {
+                    if let Err(err) = (self.on_record)(record) {
+                        *self.failure = Some(err);
+                        return Err(A::Error::custom("record rejected"));
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        let mut failure = None;
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let result = (&mut deserializer).deserialize_seq(StreamingVisitor { on_record, failure: &mut failure });
+        if let Some(err) = failure {
+            return Err(err);
+        }
+        result?;
+        if let Err(err) = deserializer.end()
+            && !lenient
+        {
+            return Err(ParseError(format!("Trailing data after the JSON array: {}", err)).into());
+        }
+        Ok(())
+    }
+
+    /// Name of the index file listing the vocabularies of an InvenioRDM fixtures directory.
+    pub const FIXTURES_INDEX: &str = "vocabularies.yaml";
+
+    /// Path of the data file for `vocab_type` inside the fixtures directory `dir`.
+    pub fn fixtures_data_path(dir: &std::path::Path, vocab_type: VocabType) -> std::path::PathBuf {
+        dir.join("vocabularies").join(format!("{}.yaml", vocab_type))
+    }
+
+    /// Add or update the `vocabularies.yaml` entry for `vocab_type` in the fixtures directory `dir`.
+    ///
+    /// Entries for other vocabularies and extra keys of the updated entry are preserved.
+    pub fn update_fixtures_index(dir: &std::path::Path, vocab_type: VocabType) -> Result<(), Box<dyn std::error::Error>> {
+        use serde_yaml::{Mapping, Value};
+
+        let index_path = dir.join(FIXTURES_INDEX);
+        let mut index: Mapping = match std::fs::read_to_string(&index_path) {
+            Ok(content) if !content.trim().is_empty() => serde_yaml::from_str(&content)?,
+            Ok(_) => Mapping::new(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Mapping::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let key = Value::String(vocab_type.to_string());
+        let mut entry = match index.remove(&key) {
+            Some(Value::Mapping(entry)) => entry,
+            _ => Mapping::new(),
+        };
+        let pid_type = Value::String("pid-type".to_string());
+        if !entry.contains_key(&pid_type) {
+            entry.insert(pid_type, Value::String(vocab_type.pid_type().to_string()));
+        }
+        entry.insert(
+            Value::String("data-file".to_string()),
+            Value::String(format!("vocabularies/{}.yaml", vocab_type)),
+        );
+        index.insert(key, Value::Mapping(entry));
+
+        std::fs::write(&index_path, serde_yaml::to_string(&index)?)?;
+        Ok(())
+    }
+
+    /// Call `on_change` once, then again each time `path` is modified, until it returns `Break`.
+    ///
+    /// Changes are reported by the platform's file notification API (inotify, FSEvents, ...).
+    /// A burst of events, such as an editor writing a file in several steps, is debounced into
+    /// one call once `interval` has passed without further events. The parent directory is
+    /// watched, so a file replaced by a rename is still followed. Where notifications are not
+    /// available, the file's modification time and size are polled every `interval` instead.
+    pub fn watch_file<F>(path: &std::path::Path, interval: std::time::Duration, mut on_change: F) -> std::io::Result<()>
+    where
+        F: FnMut() -> std::ops::ControlFlow<()>,
+    {
+        use notify::{EventKind, RecursiveMode, Watcher};
+
+        std::fs::metadata(path)?;
+        let (sender, events) = std::sync::mpsc::channel();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => std::path::Path::new("."),
+        };
+        let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        let Ok(_watcher) = watcher else {
+            return poll_file(path, interval, on_change);
+        };
+
+        if on_change().is_break() {
+            return Ok(());
+        }
+        let touches_file = |event: &notify::Event| {
+            matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any)
+                && event.paths.iter().any(|changed| changed.file_name() == path.file_name())
+        };
+        loop {
+            match events.recv() {
+                Ok(Ok(event)) if touches_file(&event) => {}
+                Ok(_) => continue,
+                Err(_) => return poll_file(path, interval, on_change),
+            }
+            // Wait for the burst to settle before converting.
+            while events.recv_timeout(interval).is_ok() {}
+            if on_change().is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Fallback for `watch_file`: poll the modification time and size of `path` every `interval`.
+    ///
+    /// A file that is briefly missing (e.g. while an editor replaces it) is waited for.
+    fn poll_file<F>(path: &std::path::Path, interval: std::time::Duration, mut on_change: F) -> std::io::Result<()>
+    where
+        F: FnMut() -> std::ops::ControlFlow<()>,
+    {
+        let stamp = |path: &std::path::Path| std::fs::metadata(path).and_then(|meta| Ok((meta.modified()?, meta.len())));
+
+        let mut last = stamp(path)?;
+        if on_change().is_break() {
+            return Ok(());
+        }
+        loop {
+            std::thread::sleep(interval);
+            let Ok(current) = stamp(path) else {
+                continue;
+            };
+            if current != last {
+                last = current;
+                if on_change().is_break() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Transparent decompression of gzip-compressed inputs such as `.json.gz` and `.csv.gz`.
+    pub mod gzip {
+        use super::ParseError;
+        use flate2::bufread::MultiGzDecoder;
+        use std::error::Error;
+        use std::io::{self, BufRead, BufReader, Read};
+        use std::path::Path;
+
+        /// The two bytes every gzip member starts with.
+        pub const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+        /// Whether `bytes` start with the gzip magic number.
+        pub fn is_gzip(bytes: &[u8]) -> bool {
+            bytes.starts_with(&MAGIC)
+        }
+
+        /// Whether the file at `path` holds gzip data, judged by its magic bytes.
+        pub fn is_gzip_file(path: &Path) -> io::Result<bool> {
+            let mut magic = [0u8; 2];
+            let read = std::fs::File::open(path)?.read(&mut magic)?;
+            Ok(is_gzip(&magic[..read]))
+        }
+
+        /// Call `read` with `reader`, decompressed on the fly when it holds gzip data.
+        ///
+        /// Concatenated members are read as one stream.
+        pub fn with_decompressed<R, T>(
+            reader: R,
+            read: impl FnOnce(&mut dyn Read) -> Result<T, Box<dyn Error>>,
+        ) -> Result<T, Box<dyn Error>>
+        where
+            R: Read,
+        {
+            let mut reader = BufReader::new(reader);
+            if is_gzip(reader.fill_buf()?) {
+                read(&mut Decompressed(MultiGzDecoder::new(reader))).map_err(unwrap_parse_error)
+            } else {
+                read(&mut reader)
+            }
+        }
+
+        /// Surface a [`ParseError`] that reached the caller wrapped in an I/O error as itself.
+        fn unwrap_parse_error(err: Box<dyn Error>) -> Box<dyn Error> {
+            let err = match err.downcast::<serde_json::Error>() {
+                Ok(err) if err.is_io() => Box::new(io::Error::from(*err)) as Box<dyn Error>,
+                Ok(err) => err,
+                Err(err) => err,
+            };
+            match err.downcast::<io::Error>() {
+                Ok(err) => match err.get_ref() {
+                    Some(inner) if inner.is::<ParseError>() => err.into_inner().expect("inner error was just checked"),
+                    _ => err,
+                },
+                Err(err) => err,
+            }
+        }
+
+        /// A gzip decoder whose corrupt or truncated streams fail as a [`ParseError`].
+        struct Decompressed<R>(MultiGzDecoder<R>);
+
+        impl<R: BufRead> Read for Decompressed<R> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.0.read(buf).map_err(|err| match err.kind() {
+                    io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                        io::Error::new(io::ErrorKind::InvalidData, ParseError(format!("Invalid gzip stream: {}", err)))
+                    }
+                    _ => err,
+                })
+            }
+        }
+    }
+
+    /// Online lookup of emitted ids against an organization registry such as the ROR API.
+    pub mod registry {
+        use std::error::Error;
+        use std::thread;
+        use std::time::{Duration, Instant};
+        use ureq::tls::{Certificate, RootCerts, TlsConfig};
+
+        /// Organizations endpoint of the public ROR API.
+        pub const DEFAULT_URL: &str = "https://api.ror.org/v2/organizations";
+
+        /// Default number of registry requests per second.
+        pub const DEFAULT_RATE: u32 = 2;
+
+        const TIMEOUT: Duration = Duration::from_secs(10);
+
+        /// Where and how fast to look ids up.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Registry {
+            /// Base URL; an id is looked up as `<url>/<id>`.
+            pub url: String,
+            /// Maximum requests per second; 0 disables rate limiting.
+            pub requests_per_second: u32,
+            /// Only look up this many ids, spread evenly over the input.
+            pub sample: Option<usize>,
+            /// DER-encoded root certificates to trust instead of the bundled web PKI roots, e.g.
+            /// for a private mirror. Empty to use the bundled roots.
+            pub root_certificates: Vec<Vec<u8>>,
+        }
+
+        impl Default for Registry {
+            fn default() -> Self {
+                Registry { url: DEFAULT_URL.to_string(), requests_per_second: DEFAULT_RATE, sample: None, root_certificates: Vec::new() }
+            }
+        }
+
+        impl Registry {
+            /// The ids of `ids` (or of their sample) that the registry does not know, in input order.
+            pub fn unknown_ids<'a>(&self, ids: &[&'a str]) -> Result<Vec<&'a str>, Box<dyn Error>> {
+                let interval = match self.requests_per_second {
+                    0 => Duration::ZERO,
+                    rate => Duration::from_secs(1) / rate,
+                };
+                let agent = self.agent();
+                let mut last_request: Option<Instant> = None;
+                let mut unknown = Vec::new();
+                for id in sample(ids, self.sample) {
+                    if let Some(last) = last_request {
+                        thread::sleep(interval.saturating_sub(last.elapsed()));
+                    }
+                    last_request = Some(Instant::now());
+                    if !self.lookup(&agent, id)? {
+                        unknown.push(id);
+                    }
+                }
+                Ok(unknown)
+            }
+
+            /// Whether the registry answers `<url>/<id>` with a success status; 404 and 410 mean it does not.
+            pub fn resolves(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+                self.lookup(&self.agent(), id)
+            }
+
+            fn lookup(&self, agent: &ureq::Agent, id: &str) -> Result<bool, Box<dyn Error>> {
+                let url = format!("{}/{}", self.url.trim_end_matches('/'), id);
+                let response = agent
+                    .get(&url)
+                    .header("Accept", "application/json")
+                    .call()
+                    .map_err(|err| std::io::Error::other(format!("Registry lookup of '{}' failed: {}", id, err)))?;
+                match response.status().as_u16() {
+                    200..=299 => Ok(true),
+                    404 | 410 => Ok(false),
+                    code => Err(std::io::Error::other(format!("Registry lookup of '{}' failed with HTTP status {}", id, code)).into()),
+                }
+            }
+
+            /// An HTTP client that follows redirects, honours the `*_PROXY` environment variables
+            /// and reports error statuses as responses so `lookup` can tell 404 from failure.
+            fn agent(&self) -> ureq::Agent {
+                let mut tls = TlsConfig::builder();
+                if !self.root_certificates.is_empty() {
+                    let roots: Vec<Certificate<'static>> =
+                        self.root_certificates.iter().map(|der| Certificate::from_der(der).to_owned()).collect();
+                    tls = tls.root_certs(RootCerts::new_with_certs(&roots));
+                }
+                ureq::Agent::config_builder()
+                    .http_status_as_error(false)
+                    .timeout_global(Some(TIMEOUT))
+                    .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+                    .tls_config(tls.build())
+                    .build()
+                    .into()
+            }
+        }
+
+        /// Every `ids` entry, or `count` of them evenly spaced so the sample covers the whole input.
+        pub fn sample<'a>(ids: &[&'a str], count: Option<usize>) -> Vec<&'a str> {
+            match count {
+                Some(count) if count < ids.len() => (0..count).map(|n| ids[n * ids.len() / count]).collect(),
+                _ => ids.to_vec(),
+            }
+        }
+    }
+
+    /// Record filters written as small boolean expressions over a record's fields.
+    ///
+    /// The grammar, from loosest to tightest binding:
+    ///
+    /// ```text
+    /// expr       := and ( "||" and )*
+    /// and        := unary ( "&&" unary )*
+    /// unary      := "!" unary | "(" expr ")" | comparison
+    /// comparison := FIELD OP VALUE
+    /// OP         := "==" | "!=" | "<" | "<=" | ">" | ">=" | "contains" | "starts_with"
+    /// VALUE      := "double-quoted string" | number
+    /// ```
+    ///
+    /// A field may hold several values (e.g. all acronyms of a record); a comparison holds if
+    /// any of them satisfies it, and `!=` holds if none equals the value. `<`, `<=`, `>` and
+    /// `>=` compare numerically and never hold for values that are not numbers.
+    pub mod filter {
+        /// A comparison operator.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Op {
+            Eq,
+            Ne,
+            Lt,
+            Le,
+            Gt,
+            Ge,
+            Contains,
+            StartsWith,
+        }
+
+        /// A parsed filter expression.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Expr {
+            Or(Box<Expr>, Box<Expr>),
+            And(Box<Expr>, Box<Expr>),
+            Not(Box<Expr>),
+            Compare { field: String, op: Op, value: String },
+        }
+
+        impl Expr {
+            /// Whether a record matches, given the values of each of its fields by name.
+            pub fn matches<'a, F: Fn(&str) -> Vec<&'a str>>(&self, values: &F) -> bool {
+                match self {
+                    Expr::Or(left, right) => left.matches(values) || right.matches(values),
+                    Expr::And(left, right) => left.matches(values) && right.matches(values),
+                    Expr::Not(inner) => !inner.matches(values),
+                    Expr::Compare { field, op: Op::Ne, value } => !values(field).contains(&value.as_str()),
+                    Expr::Compare { field, op, value } => values(field).iter().any(|candidate| compare(candidate, *op, value)),
+                }
+            }
+
+            /// The names of every field the expression reads, in order of appearance.
+            pub fn fields(&self) -> Vec<&str> {
+                match self {
+                    Expr::Or(left, right) | Expr::And(left, right) => {
+                        let mut fields = left.fields();
+                        fields.extend(right.fields());
+                        fields
+                    }
+                    Expr::Not(inner) => inner.fields(),
+                    Expr::Compare { field, .. } => vec![field.as_str()],
+                }
+            }
+        }
+
+        fn compare(candidate: &str, op: Op, value: &str) -> bool {
+            let numbers = || Some((candidate.trim().parse::<f64>().ok()?, value.parse::<f64>().ok()?));
+            match op {
+                Op::Eq => candidate == value,
+                Op::Ne => candidate != value,
+                Op::Contains => candidate.contains(value),
+                Op::StartsWith => candidate.starts_with(value),
+                Op::Lt => numbers().is_some_and(|(a, b)| a < b),
+                Op::Le => numbers().is_some_and(|(a, b)| a <= b),
+                Op::Gt => numbers().is_some_and(|(a, b)| a > b),
+                Op::Ge => numbers().is_some_and(|(a, b)| a >= b),
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Token {
+            Ident(String),
+            Value(String),
+            Op(Op),
+            And,
+            Or,
+            Not,
+            Open,
+            Close,
+        }
+
+        impl std::str::FromStr for Expr {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let tokens = tokenize(s)?;
+                let mut parser = Parser { tokens: &tokens, pos: 0 };
+                let expr = parser.or()?;
+                match parser.tokens.get(parser.pos) {
+                    None => Ok(expr),
+                    Some(token) => Err(format!("unexpected {:?} after a complete expression", token)),
+                }
+            }
+        }
+
+        fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+            let chars: Vec<char> = s.chars().collect();
+            let mut tokens = Vec::new();
+            let mut i = 0;
+            while i < chars.len() {
+                let next = chars.get(i + 1).copied();
+                let (token, len) = match chars[i] {
+                    c if c.is_whitespace() => {
+                        i += 1;
+                        continue;
+                    }
+                    '(' => (Token::Open, 1),
+                    ')' => (Token::Close, 1),
+                    '&' if next == Some('&') => (Token::And, 2),
+                    '|' if next == Some('|') => (Token::Or, 2),
+                    '=' if next == Some('=') => (Token::Op(Op::Eq), 2),
+                    '!' if next == Some('=') => (Token::Op(Op::Ne), 2),
+                    '!' => (Token::Not, 1),
+                    '<' if next == Some('=') => (Token::Op(Op::Le), 2),
+                    '<' => (Token::Op(Op::Lt), 1),
+                    '>' if next == Some('=') => (Token::Op(Op::Ge), 2),
+                    '>' => (Token::Op(Op::Gt), 1),
+                    '"' => {
+                        let mut value = String::new();
+                        let mut j = i + 1;
+                        loop {
+                            match chars.get(j) {
+                                None => return Err("unterminated string".to_string()),
+                                Some('"') => break,
+                                Some('\\') if j + 1 < chars.len() => {
+                                    value.push(chars[j + 1]);
+                                    j += 2;
+                                }
+                                Some(&c) => {
+                                    value.push(c);
+                                    j += 1;
+                                }
+                            }
+                        }
+                        (Token::Value(value), j + 1 - i)
+                    }
+                    c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                        let len = 1 + chars[i + 1..].iter().take_while(|c| c.is_ascii_digit() || **c == '.').count();
+                        (Token::Value(chars[i..i + len].iter().collect()), len)
+                    }
+                    c if c.is_ascii_alphabetic() || c == '_' => {
+                        let len = chars[i..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == '_').count();
+                        let word: String = chars[i..i + len].iter().collect();
+                        let token = match word.as_str() {
+                            "contains" => Token::Op(Op::Contains),
+                            "starts_with" => Token::Op(Op::StartsWith),
+                            _ => Token::Ident(word),
+                        };
+                        (token, len)
+                    }
+                    c => return Err(format!("unexpected character '{}'", c)),
+                };
+                tokens.push(token);
+                i += len;
+            }
+            Ok(tokens)
+        }
+
+        struct Parser<'a> {
+            tokens: &'a [Token],
+            pos: usize,
+        }
+
+        impl Parser<'_> {
+            fn next(&mut self) -> Option<Token> {
+                let token = self.tokens.get(self.pos).cloned();
+                self.pos += 1;
+                token
+            }
+
+            fn eat(&mut self, token: &Token) -> bool {
+                let found = self.tokens.get(self.pos) == Some(token);
+                if found {
+                    self.pos += 1;
+                }
+                found
+            }
+
+            fn or(&mut self) -> Result<Expr, String> {
+                let mut expr = self.and()?;
+                while self.eat(&Token::Or) {
+                    expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+                }
+                Ok(expr)
+            }
+
+            fn and(&mut self) -> Result<Expr, String> {
+                let mut expr = self.unary()?;
+                while self.eat(&Token::And) {
+                    expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+                }
+                Ok(expr)
+            }
+
+            fn unary(&mut self) -> Result<Expr, String> {
+                match self.next() {
+                    Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+                    Some(Token::Open) => {
+                        let expr = self.or()?;
+                        if !self.eat(&Token::Close) {
+                            return Err("missing ')'".to_string());
+                        }
+                        Ok(expr)
+                    }
+                    Some(Token::Ident(field)) => {
+                        let Some(Token::Op(op)) = self.next() else {
+                            return Err(format!("expected an operator after '{}'", field));
+                        };
+                        let Some(Token::Value(value)) = self.next() else {
+                            return Err(format!("expected a quoted string or number after '{}' {:?}", field, op));
+                        };
+                        Ok(Expr::Compare { field, op, value })
+                    }
+                    Some(token) => Err(format!("expected a field, '!' or '(' but found {:?}", token)),
+                    None => Err("expected a field, '!' or '(' but the expression ended".to_string()),
+                }
+            }
+        }
+    }
+
+    /// Manifests listing several conversions to run in one invocation.
+    pub mod manifest {
+        use super::{ParseError, VocabType};
+        use serde::Deserialize;
+        use std::error::Error;
+        use std::path::{Path, PathBuf};
+
+        /// One conversion listed in a manifest.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct ManifestEntry {
+            /// Vocabulary type to convert.
+            pub vocab_type: VocabType,
+            /// Input file, resolved against the manifest's directory.
+            pub input: PathBuf,
+            /// Output file, resolved against the manifest's directory.
+            pub output: PathBuf,
+            /// Command-line options applied to this conversion only, e.g. `["--all-acronyms"]`.
+            pub options: Vec<String>,
+        }
+
+        /// A manifest document as written: a list of `[[vocabulary]]` tables.
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Manifest {
+            #[serde(default)]
+            vocabulary: Vec<VocabularyTable>,
+        }
+
+        /// One `[[vocabulary]]` table, with paths as written in the manifest.
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct VocabularyTable {
+            #[serde(rename = "type")]
+            vocab_type: String,
+            input: PathBuf,
+            output: PathBuf,
+            #[serde(default)]
+            options: Vec<String>,
+        }
+
+        /// Read the manifest at `path`, resolving relative paths against its directory.
+        pub fn read_manifest(path: &Path) -> Result<Vec<ManifestEntry>, Box<dyn Error>> {
+            let text = std::fs::read_to_string(path)?;
+            let base = path.parent().unwrap_or_else(|| Path::new(""));
+            parse_manifest(&text, base)
+        }
+
+        /// Parse a TOML manifest.
+        ///
+        /// Each conversion is a `[[vocabulary]]` table with string keys `type`, `input` and
+        /// `output`, and an optional string array `options`:
+        ///
+        /// ```toml
+        /// [[vocabulary]]
+        /// type = "affiliations"
+        /// input = "ror.json"
+        /// output = "affiliations.yaml"
+        /// options = ["--all-acronyms"]
+        /// ```
+        pub fn parse_manifest(text: &str, base: &Path) -> Result<Vec<ManifestEntry>, Box<dyn Error>> {
+            let manifest: Manifest = toml::from_str(text).map_err(|err| ParseError(format!("Invalid manifest: {}", err)))?;
+            manifest
+                .vocabulary
+                .into_iter()
+                .enumerate()
+                .map(|(index, table)| {
+                    let vocab_type: VocabType = table
+                        .vocab_type
+                        .parse()
+                        .map_err(|err| ParseError(format!("Manifest vocabulary {}: {}", index + 1, err)))?;
+                    Ok(ManifestEntry {
+                        vocab_type,
+                        input: base.join(table.input),
+                        output: base.join(table.output),
+                        options: table.options,
+                    })
+                })
+                .collect()
+        }
+    }
+
     // Module for converting an Affiliations vocabulary.
     pub mod affiliations {
-        use deunicode::deunicode;
+        use super::{ConversionReport, ParseError, UnsupportedError, ValidationError, Verbosity, VocabType, Warning, WarningKind};
+        use deunicode::{deunicode, deunicode_char, deunicode_with_tofu};
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+        use unicode_normalization::char::is_combining_mark;
+        use unicode_normalization::UnicodeNormalization;
+        use std::collections::{BTreeMap, HashMap};
+        use std::error::Error;
+        use std::fs::File;
+        use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+        use std::path::Path;
+
+        #[derive(Debug, Default, Deserialize, JsonSchema)]
+        pub struct AffiliationItem {
+            /// Record id, e.g. a ROR URL; the last path segment is emitted.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub id: String,
+            /// Display name.
+            #[serde(deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub name: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<Label>>")]
+            pub labels: Vec<Label>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<String>>")]
+            pub acronyms: Vec<String>,
+            /// ISO 3166-1 alpha-2 country code.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub country_code: String,
+            /// Year the organization was established, as given by ROR.
+            #[serde(default)]
+            pub established: Option<i32>,
+            /// Web links, either plain URLs (ROR v1) or typed `{ type, value }` objects (ROR v2).
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<Link>>")]
+            pub links: Vec<Link>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub wikipedia_url: String,
+            /// Ids of the organization in other registries (GRID, ISNI, Wikidata, FundRef).
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<ExternalIds>")]
+            pub external_ids: ExternalIds,
+            /// Organization types, e.g. `["Education"]`.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<String>>")]
+            pub types: Vec<String>,
+        }
+
+        /// Fields a `--record-filter` expression can read from an affiliation record.
+        pub const FILTER_FIELDS: &[&str] = &["id", "name", "country", "acronym", "label", "type", "established"];
+
+        impl AffiliationItem {
+            /// The record's values of the `--record-filter` field `field`, as given in the input.
+            pub fn filter_values(&self, field: &str) -> Vec<&str> {
+                match field {
+                    "id" => vec![self.id.as_str()],
+                    "name" => vec![self.name.as_str()],
+                    "country" => vec![self.country_code.as_str()],
+                    "acronym" => self.acronyms.iter().map(String::as_str).collect(),
+                    "label" => self.labels.iter().map(|label| label.label.as_str()).collect(),
+                    "type" => self.types.iter().map(String::as_str).collect(),
+                    _ => Vec::new(),
+                }
+            }
+        }
+
+        /// A `--record-filter` expression over the `FILTER_FIELDS` of affiliation records.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct RecordFilter(pub super::filter::Expr);
+
+        impl RecordFilter {
+            /// Whether `item` is kept by the filter.
+            pub fn matches(&self, item: &AffiliationItem) -> bool {
+                // `established` is numeric, so it is rendered for the comparison.
+                let established = item.established.map(|year| year.to_string()).unwrap_or_default();
+                self.0.matches(&|field: &str| match field {
+                    "established" if !established.is_empty() => vec![established.as_str()],
+                    _ => item.filter_values(field),
+                })
+            }
+        }
+
+        impl std::str::FromStr for RecordFilter {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let expr: super::filter::Expr = s.parse().map_err(|err| format!("Invalid record filter: {}", err))?;
+                if let Some(field) = expr.fields().into_iter().find(|field| !FILTER_FIELDS.contains(field)) {
+                    return Err(format!("Invalid record filter: unknown field '{}' (expected {})", field, FILTER_FIELDS.join(", ")));
+                }
+                Ok(RecordFilter(expr))
+            }
+        }
+
+        /// ROR `external_ids`: an object keyed by id type (ROR v1) or a list of typed groups (ROR v2).
+        #[derive(Debug, Clone, Deserialize, JsonSchema)]
+        #[serde(untagged)]
+        pub enum ExternalIds {
+            /// `{ "GRID": { "preferred": ..., "all": ... }, "Wikidata": [...] }`
+            ByType(BTreeMap<String, ExternalIdValues>),
+            /// `[{ "type": "wikidata", "preferred": ..., "all": [...] }]`
+            Typed(Vec<TypedExternalIds>),
+        }
+
+        impl Default for ExternalIds {
+            fn default() -> Self {
+                ExternalIds::ByType(BTreeMap::new())
+            }
+        }
+
+        impl ExternalIds {
+            /// `(type, id)` pairs, each type's preferred id first, without repeats.
+            pub fn ids(&self) -> Vec<(&str, &str)> {
+                let groups: Vec<(&str, &ExternalIdValues)> = match self {
+                    ExternalIds::ByType(groups) => groups.iter().map(|(kind, values)| (kind.as_str(), values)).collect(),
+                    ExternalIds::Typed(groups) => groups.iter().map(|group| (group.kind.as_str(), &group.values)).collect(),
+                };
+                let mut ids: Vec<(&str, &str)> = Vec::new();
+                for (kind, values) in groups {
+                    for value in values.values().into_iter().map(str::trim) {
+                        if !value.is_empty() && !ids.contains(&(kind, value)) {
+                            ids.push((kind, value));
+                        }
+                    }
+                }
+                ids
+            }
+        }
+
+        /// The ids of one external id type.
+        #[derive(Debug, Clone, Deserialize, JsonSchema)]
+        #[serde(untagged)]
+        pub enum ExternalIdValues {
+            /// `{ "preferred": "Q42", "all": ["Q42", "Q43"] }`; `all` may also be a single string.
+            Grouped {
+                #[serde(default)]
+                preferred: Option<String>,
+                #[serde(default)]
+                all: Option<OneOrMany>,
+            },
+            /// A plain array of ids.
+            List(Vec<String>),
+            /// A single id.
+            Single(String),
+        }
+
+        impl ExternalIdValues {
+            /// The preferred id, if any, followed by all ids.
+            pub fn values(&self) -> Vec<&str> {
+                match self {
+                    ExternalIdValues::Grouped { preferred, all } => preferred
+                        .as_deref()
+                        .into_iter()
+                        .chain(all.iter().flat_map(OneOrMany::iter))
+                        .collect(),
+                    ExternalIdValues::List(values) => values.iter().map(String::as_str).collect(),
+                    ExternalIdValues::Single(value) => vec![value.as_str()],
+                }
+            }
+        }
+
+        /// One ROR v2 `external_ids` group.
+        #[derive(Debug, Clone, Deserialize, JsonSchema)]
+        pub struct TypedExternalIds {
+            #[serde(rename = "type", deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub kind: String,
+            #[serde(flatten)]
+            pub values: ExternalIdValues,
+        }
+
+        /// A value given either on its own or as a list.
+        #[derive(Debug, Clone, Deserialize, JsonSchema)]
+        #[serde(untagged)]
+        pub enum OneOrMany {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        impl OneOrMany {
+            pub fn iter(&self) -> impl Iterator<Item = &str> {
+                match self {
+                    OneOrMany::One(value) => std::slice::from_ref(value).iter(),
+                    OneOrMany::Many(values) => values.iter(),
+                }
+                .map(String::as_str)
+            }
+        }
+
+        /// A web link of an organization record.
+        #[derive(Debug, Clone, Deserialize, JsonSchema)]
+        #[serde(untagged)]
+        pub enum Link {
+            /// A bare URL, as in ROR v1 `links`.
+            Url(String),
+            /// A typed link, as in ROR v2 `links` (e.g. `{ "type": "website", "value": "..." }`).
+            Typed {
+                #[serde(rename = "type", default, deserialize_with = "deserialize_null_default")]
+                #[schemars(with = "Nullable<String>")]
+                kind: String,
+                #[serde(deserialize_with = "deserialize_null_default")]
+                #[schemars(with = "Nullable<String>")]
+                value: String,
+            },
+        }
+
+        impl Link {
+            /// Whether the link points at the organization's own website.
+            pub fn is_website(&self) -> bool {
+                match self {
+                    Link::Url(_) => true,
+                    Link::Typed { kind, .. } => kind.is_empty() || kind == "website",
+                }
+            }
+
+            /// The link's URL.
+            pub fn url(&self) -> &str {
+                match self {
+                    Link::Url(url) | Link::Typed { value: url, .. } => url,
+                }
+            }
+        }
+
+        #[derive(Debug, Deserialize, JsonSchema)]
+        pub struct Label {
+            /// Language code, e.g. `en` or `pt-BR`.
+            #[serde(deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub iso639: String,
+            #[serde(deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub label: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<bool>")]
+            pub preferred: bool,
+        }
+
+        /// Mirror of `AffiliationItem` that rejects unknown keys, used by `--strict-fields`.
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct StrictAffiliationItem {
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            id: String,
+            #[serde(deserialize_with = "deserialize_null_default")]
+            name: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            labels: Vec<StrictLabel>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            acronyms: Vec<String>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            country_code: String,
+            #[serde(default)]
+            established: Option<i32>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            links: Vec<Link>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            wikipedia_url: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            external_ids: ExternalIds,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            types: Vec<String>,
+        }
+
+        /// Mirror of `Label` that rejects unknown keys, used by `--strict-fields`.
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct StrictLabel {
+            #[serde(deserialize_with = "deserialize_null_default")]
+            iso639: String,
+            #[serde(deserialize_with = "deserialize_null_default")]
+            label: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            preferred: bool,
+        }
+
+        impl From<StrictAffiliationItem> for AffiliationItem {
+            fn from(item: StrictAffiliationItem) -> Self {
+                AffiliationItem {
+                    id: item.id,
+                    name: item.name,
+                    labels: item.labels.into_iter().map(Label::from).collect(),
+                    acronyms: item.acronyms,
+                    country_code: item.country_code,
+                    established: item.established,
+                    links: item.links,
+                    wikipedia_url: item.wikipedia_url,
+                    external_ids: item.external_ids,
+                    types: item.types,
+                }
+            }
+        }
+
+        impl From<StrictLabel> for Label {
+            fn from(label: StrictLabel) -> Self {
+                Label {
+                    iso639: label.iso639,
+                    label: label.label,
+                    preferred: label.preferred,
+                }
+            }
+        }
+
+        /// Schema of a field read with `deserialize_null_default`: the value or `null`.
+        ///
+        /// Unlike `Option<T>`, it leaves the field required unless it also has a serde default.
+        pub struct Nullable<T>(std::marker::PhantomData<T>);
+
+        impl<T: JsonSchema> JsonSchema for Nullable<T> {
+            fn inline_schema() -> bool {
+                true
+            }
+
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                format!("Nullable_{}", T::schema_name()).into()
+            }
+
+            fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+                <Option<T>>::json_schema(generator)
+            }
+        }
+
+        pub fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+        where
+            T: Default + Deserialize<'de>,
+            D: serde::Deserializer<'de>,
+        {
+            let opt = Option::deserialize(deserializer)?;
+            Ok(opt.unwrap_or_default())
+        }
+
+        /// Sanitize a string by transliterating ambiguous Unicode characters (such as Cyrillic)
+        /// into their approximate ASCII equivalents.
+        pub fn sanitize(s: &str) -> String {
+            deunicode(s)
+        }
+
+        /// Strip accents from Latin letters (`é` → `e`), leaving every other character,
+        /// including non-Latin scripts, untouched.
+        ///
+        /// The text is NFD-decomposed and the combining marks that follow a Latin letter are
+        /// dropped, so precomposed (`Ơ`) and decomposed (`O` + U+031B) input fold alike. Letters
+        /// without a canonical decomposition (`ß`, `ø`, `ł`, `æ`) are kept as they are, and marks
+        /// on other scripts are recomposed (NFC) so `й` stays `й`.
+        pub fn fold_diacritics(s: &str) -> String {
+            let mut latin_base = false;
+            let folded: String = s
+                .nfd()
+                .filter(|&c| {
+                    if is_combining_mark(c) {
+                        return !latin_base;
+                    }
+                    latin_base = is_latin_letter(c);
+                    true
+                })
+                .collect();
+            folded.nfc().collect()
+        }
+
+        /// Whether `c` is a letter of the Latin script blocks.
+        fn is_latin_letter(c: char) -> bool {
+            c.is_alphabetic()
+                && matches!(c,
+                    'A'..='Z' | 'a'..='z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' | '\u{2C60}'..='\u{2C7F}' | '\u{A720}'..='\u{A7FF}'
+                )
+        }
+
+        /// Remove HTML tags and decode common HTML entities, leaving plain text.
+        pub fn strip_html(s: &str) -> String {
+            let mut text = String::with_capacity(s.len());
+            let mut in_tag = false;
+            for c in s.chars() {
+                match c {
+                    '<' => in_tag = true,
+                    '>' if in_tag => in_tag = false,
+                    _ if !in_tag => text.push(c),
+                    _ => {}
+                }
+            }
+            decode_html_entities(&text)
+        }
+
+        fn decode_html_entities(s: &str) -> String {
+            let mut out = String::with_capacity(s.len());
+            let mut rest = s;
+            while let Some(start) = rest.find('&') {
+                out.push_str(&rest[..start]);
+                let tail = &rest[start..];
+                let decoded = tail.find(';').and_then(|end| {
+                    let entity = &tail[1..end];
+                    let c = match entity.strip_prefix('#') {
+                        Some(num) => match num.strip_prefix(['x', 'X']) {
+                            Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                            None => num.parse::<u32>().ok().and_then(char::from_u32),
+                        },
+                        None => named_entity(entity),
+                    };
+                    c.map(|c| (c, end))
+                });
+                match decoded {
+                    Some((c, end)) => {
+                        out.push(c);
+                        rest = &tail[end + 1..];
+                    }
+                    None => {
+                        out.push('&');
+                        rest = &tail[1..];
+                    }
+                }
+            }
+            out.push_str(rest);
+            out
+        }
+
+        fn named_entity(name: &str) -> Option<char> {
+            let c = match name {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                "aacute" => 'á',
+                "agrave" => 'à',
+                "acirc" => 'â',
+                "auml" => 'ä',
+                "aring" => 'å',
+                "ccedil" => 'ç',
+                "eacute" => 'é',
+                "egrave" => 'è',
+                "ecirc" => 'ê',
+                "euml" => 'ë',
+                "iacute" => 'í',
+                "igrave" => 'ì',
+                "icirc" => 'î',
+                "iuml" => 'ï',
+                "ntilde" => 'ñ',
+                "oacute" => 'ó',
+                "ograve" => 'ò',
+                "ocirc" => 'ô',
+                "ouml" => 'ö',
+                "oslash" => 'ø',
+                "uacute" => 'ú',
+                "ugrave" => 'ù',
+                "ucirc" => 'û',
+                "uuml" => 'ü',
+                "szlig" => 'ß',
+                "Aacute" => 'Á',
+                "Auml" => 'Ä',
+                "Aring" => 'Å',
+                "Eacute" => 'É',
+                "Ouml" => 'Ö',
+                "Uuml" => 'Ü',
+                _ => return None,
+            };
+            Some(c)
+        }
+
+        /// What to do with records whose sanitized name is empty.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum EmptyNamePolicy {
+            /// Drop the record from the output.
+            Skip,
+            /// Emit the record with an empty name.
+            #[default]
+            Keep,
+            /// Abort the conversion with an error.
+            Error,
+        }
+
+        impl std::str::FromStr for EmptyNamePolicy {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "skip" => Ok(EmptyNamePolicy::Skip),
+                    "keep" => Ok(EmptyNamePolicy::Keep),
+                    "error" => Ok(EmptyNamePolicy::Error),
+                    other => Err(format!("Invalid empty-name policy: {} (expected skip, keep or error)", other)),
+                }
+            }
+        }
+
+        /// Serialization of the written entries.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum OutputFormat {
+            /// A YAML list, as imported by InvenioRDM.
+            #[default]
+            Yaml,
+            /// One compact JSON object per line.
+            JsonLines,
+        }
+
+        impl std::str::FromStr for OutputFormat {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "yaml" => Ok(OutputFormat::Yaml),
+                    "jsonl" | "ndjson" => Ok(OutputFormat::JsonLines),
+                    other => Err(format!("Invalid output format: {} (expected yaml or jsonl)", other)),
+                }
+            }
+        }
+
+        /// Line ending of the written YAML.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum Newline {
+            #[default]
+            Lf,
+            Crlf,
+        }
+
+        impl Newline {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    Newline::Lf => "\n",
+                    Newline::Crlf => "\r\n",
+                }
+            }
+        }
+
+        impl std::str::FromStr for Newline {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "lf" => Ok(Newline::Lf),
+                    "crlf" => Ok(Newline::Crlf),
+                    other => Err(format!("Invalid newline: {} (expected lf or crlf)", other)),
+                }
+            }
+        }
+
+        /// A personal field of names entries that `--redact` can replace with a placeholder.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum RedactedField {
+            GivenName,
+            FamilyName,
+        }
+
+        impl std::str::FromStr for RedactedField {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "given_name" => Ok(RedactedField::GivenName),
+                    "family_name" => Ok(RedactedField::FamilyName),
+                    other => Err(format!("Invalid redacted field: {} (expected given_name or family_name)", other)),
+                }
+            }
+        }
+
+        /// How names entries combine the given and family names into `name`.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum NameFormat {
+            /// "Family, Given", InvenioRDM's default.
+            #[default]
+            FamilyCommaGiven,
+            /// "Given Family".
+            GivenFamily,
+        }
+
+        impl std::str::FromStr for NameFormat {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "family-comma-given" => Ok(NameFormat::FamilyCommaGiven),
+                    "given-family" => Ok(NameFormat::GivenFamily),
+                    other => Err(format!("Invalid name format: {} (expected family-comma-given or given-family)", other)),
+                }
+            }
+        }
+
+        /// The format of the input file.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum InputFormat {
+            /// Detected from the content: JSON, JSON Lines, delimited text, Parquet or SQLite.
+            Auto,
+            /// A JSON array of records.
+            #[default]
+            Json,
+            /// One JSON record per line.
+            JsonLines,
+            /// Delimited text with a header row (comma-separated by default).
+            Csv,
+            /// An Apache Parquet file whose columns are mapped by name, like CSV columns.
+            Parquet,
+            /// An SQLite database whose rows are selected by `--query` and mapped by column name.
+            Sqlite,
+        }
+
+        impl std::str::FromStr for InputFormat {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "auto" => Ok(InputFormat::Auto),
+                    "json" => Ok(InputFormat::Json),
+                    "jsonl" | "ndjson" => Ok(InputFormat::JsonLines),
+                    "csv" | "tsv" => Ok(InputFormat::Csv),
+                    "parquet" => Ok(InputFormat::Parquet),
+                    "sqlite" => Ok(InputFormat::Sqlite),
+                    other => Err(format!("Invalid input format: {} (expected auto, json, jsonl, csv, parquet or sqlite)", other)),
+                }
+            }
+        }
+
+        /// Which acronym fields to emit for an affiliation.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum AcronymStrategy {
+            /// Only the first usable acronym, as `acronym`.
+            #[default]
+            First,
+            /// `acronym` plus every distinct acronym in an `acronyms` list.
+            All,
+            /// `acronym` plus every distinct acronym recorded as `props.title_variants`.
+            TitleVariants,
+            /// Both the `acronyms` list and `props.title_variants`.
+            AllAndVariants,
+        }
+
+        impl AcronymStrategy {
+            /// Whether the `acronyms` list is emitted.
+            pub fn lists_all(&self) -> bool {
+                matches!(self, AcronymStrategy::All | AcronymStrategy::AllAndVariants)
+            }
+
+            /// Whether the acronyms are recorded as title variants in `props`.
+            pub fn adds_title_variants(&self) -> bool {
+                matches!(self, AcronymStrategy::TitleVariants | AcronymStrategy::AllAndVariants)
+            }
+        }
+
+        impl std::str::FromStr for AcronymStrategy {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "first" => Ok(AcronymStrategy::First),
+                    "all" => Ok(AcronymStrategy::All),
+                    "title-variants" => Ok(AcronymStrategy::TitleVariants),
+                    "all-and-variants" => Ok(AcronymStrategy::AllAndVariants),
+                    other => Err(format!(
+                        "Invalid acronym strategy: {} (expected first, all, title-variants or all-and-variants)",
+                        other
+                    )),
+                }
+            }
+        }
+
+        /// Which usable acronym becomes the primary `acronym` when a record has several.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum AcronymPick {
+            /// The first one in input order.
+            #[default]
+            First,
+            /// The one with the fewest characters; ties go to the earlier one.
+            Shortest,
+            /// The one with the most characters; ties go to the earlier one.
+            Longest,
+        }
+
+        impl AcronymPick {
+            /// The chosen acronym among `acronyms`, which are in input order.
+            pub fn pick(&self, acronyms: impl IntoIterator<Item = String>) -> Option<String> {
+                let mut acronyms = acronyms.into_iter();
+                let first = acronyms.next()?;
+                Some(acronyms.fold(first, |chosen, acronym| {
+                    let (length, chosen_length) = (acronym.chars().count(), chosen.chars().count());
+                    let better = match self {
+                        AcronymPick::First => false,
+                        AcronymPick::Shortest => length < chosen_length,
+                        AcronymPick::Longest => length > chosen_length,
+                    };
+                    if better { acronym } else { chosen }
+                }))
+            }
+        }
+
+        impl std::str::FromStr for AcronymPick {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "first" => Ok(AcronymPick::First),
+                    "shortest" => Ok(AcronymPick::Shortest),
+                    "longest" => Ok(AcronymPick::Longest),
+                    other => Err(format!("Invalid acronym pick: {} (expected shortest, longest or first)", other)),
+                }
+            }
+        }
+
+        /// Which top-level `YamlEntry` fields to emit.
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub enum FieldProjection {
+            /// Emit every field.
+            #[default]
+            All,
+            /// Emit only the listed fields.
+            Include(Vec<String>),
+            /// Emit every field except the listed ones.
+            Exclude(Vec<String>),
+        }
+
+        impl FieldProjection {
+            /// Whether `field` survives the projection.
+            pub fn keeps(&self, field: &str) -> bool {
+                match self {
+                    FieldProjection::All => true,
+                    FieldProjection::Include(fields) => fields.iter().any(|f| f == field),
+                    FieldProjection::Exclude(fields) => !fields.iter().any(|f| f == field),
+                }
+            }
+        }
+
+        /// The top-level shape of a JSON input document.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum InputShape {
+            /// An array of records.
+            #[default]
+            Array,
+            /// An object mapping each record's id to the record.
+            Map,
+        }
+
+        impl std::str::FromStr for InputShape {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "array" => Ok(InputShape::Array),
+                    "map" => Ok(InputShape::Map),
+                    other => Err(format!("Invalid input shape: {} (expected array or map)", other)),
+                }
+            }
+        }
+
+        /// How to handle characters `deunicode` cannot transliterate.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum DeunicodeErrors {
+            /// Remove the character.
+            Strip,
+            /// Replace the character with the `[?]` placeholder.
+            #[default]
+            Placeholder,
+            /// Fail the conversion.
+            Error,
+        }
+
+        impl std::str::FromStr for DeunicodeErrors {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "strip" => Ok(DeunicodeErrors::Strip),
+                    "placeholder" => Ok(DeunicodeErrors::Placeholder),
+                    "error" => Ok(DeunicodeErrors::Error),
+                    other => Err(format!("Invalid deunicode error policy: {} (expected strip, placeholder or error)", other)),
+                }
+            }
+        }
+
+        /// Return the first character of `s` that `deunicode` cannot transliterate.
+        pub fn unmappable_char(s: &str) -> Option<char> {
+            s.chars().find(|c| deunicode_char(*c).is_none())
+        }
+
+        /// Options controlling how records are cleaned, converted and written.
+        #[derive(Debug, Clone)]
+        pub struct ConvertOptions {
+            /// Format of the input file.
+            pub input_format: InputFormat,
+            /// Field delimiter used when reading CSV input.
+            pub delimiter: char,
+            /// Top-level shape of JSON input.
+            pub input_shape: InputShape,
+            /// Dotted path to the records within a wrapping JSON document (e.g. `data.items`).
+            pub records_path: Option<String>,
+            /// Query selecting `AffiliationItem` columns from an SQLite input.
+            pub sqlite_query: Option<String>,
+            /// Strip HTML tags and decode HTML entities before sanitizing.
+            pub strip_html: bool,
+            /// Identifier scheme overriding the vocabulary's default scheme.
+            pub id_scheme: Option<String>,
+            /// Emit schemes as given instead of lowercasing them.
+            pub preserve_scheme_case: bool,
+            /// Disposition of records whose sanitized name is empty.
+            pub on_empty_name: EmptyNamePolicy,
+            /// Use the id's last path segment as the name of records with no name and no labels.
+            pub name_from_id_fallback: bool,
+            /// Split acronyms joined by commas or semicolons into separate acronyms.
+            pub split_acronyms: bool,
+            /// Names fields replaced with a placeholder in the output.
+            pub redact: Vec<RedactedField>,
+            /// How names entries compose `name` from the given and family names.
+            pub name_format: NameFormat,
+            /// Constant `(language, value)` pairs inserted into every entry's title map.
+            pub extra_titles: Vec<(String, String)>,
+            /// Title languages to keep; others are dropped, except the primary `en` title.
+            pub lang_allowlist: Option<Vec<String>>,
+            /// Drop non-primary titles whose value equals the primary `en` title.
+            pub dedupe_titles: bool,
+            /// Merge dialect labels such as `pt-BR` and `pt-PT` into a single base-language title.
+            pub collapse_dialects: bool,
+            /// Create the output file's missing parent directories instead of failing.
+            pub create_dirs: bool,
+            /// Second path that receives a byte-identical copy of the output.
+            pub tee: Option<std::path::PathBuf>,
+            /// Supplementary titles keyed by record id, merged over the source labels.
+            pub included_labels: HashMap<String, BTreeMap<String, String>>,
+            /// Manual field corrections keyed by record id, applied over the converted entry.
+            pub overrides: HashMap<String, EntryOverride>,
+            /// Accept trailing data after the JSON array, parsing only the first document.
+            pub lenient: bool,
+            /// Fields of each entry to keep when serializing.
+            pub fields: FieldProjection,
+            /// Use a label marked `preferred` as the display name instead of `name`.
+            pub respect_preferred: bool,
+            /// Diagnostic output level.
+            pub verbosity: Verbosity,
+            /// Reject input records carrying keys the converter does not know.
+            pub strict_fields: bool,
+            /// Write each entry as a single-line flow mapping.
+            pub compact_yaml: bool,
+            /// Which acronym fields to emit alongside `acronym`.
+            pub acronym_strategy: AcronymStrategy,
+            /// How the primary `acronym` is chosen among several usable ones.
+            pub acronym_pick: AcronymPick,
+            /// Acronyms longer than this many characters are dropped as likely full names.
+            pub max_acronym_length: usize,
+            /// Sort the emitted `acronyms` list lexicographically.
+            pub alphabetize_acronyms: bool,
+            /// Emit subjects as SKOS-style concepts instead of the InvenioRDM shape.
+            pub skos: bool,
+            /// Add a `country_name` derived from the record's ISO 3166 country code.
+            pub emit_country_name: bool,
+            /// Remove legal-form suffixes ("GmbH", "Inc.") from the display name.
+            pub strip_legal_suffixes: bool,
+            /// Key under which the identifiers list is emitted (`identifiers` or, for older
+            /// InvenioRDM versions, `identifier`).
+            pub identifier_key: String,
+            /// Emit the full identifier URL rather than its last path segment.
+            pub full_identifier: bool,
+            /// Base URL used to rebuild full identifiers from the extracted segment.
+            pub identifier_base: Option<String>,
+            /// Namespace prepended to every emitted entry `id` (not to identifier values).
+            pub entry_id_prefix: Option<String>,
+            /// Version of the source registry snapshot, written as a header comment.
+            pub vocab_version: Option<String>,
+            /// Also record `vocab_version` in each entry's `props`.
+            pub version_in_props: bool,
+            /// Re-read and parse the output after writing it.
+            pub verify_output: bool,
+            /// Unix permission bits (e.g. `0o644`) to set on the output file after writing.
+            pub output_mode: Option<u32>,
+            /// Derive an id from the name for records that have none.
+            pub generate_ids: bool,
+            /// Seed mixed into the suffix of colliding generated ids; `None` uses `-2`, `-3`, ...
+            pub seed: Option<u64>,
+            /// Handling of characters that cannot be transliterated.
+            pub deunicode_errors: DeunicodeErrors,
+            /// Only strip diacritics from Latin letters instead of fully transliterating.
+            pub fold_diacritics_only: bool,
+            /// Check the whole input is valid UTF-8 before parsing it.
+            pub strict_utf8: bool,
+            /// Fail on duplicate identifier values instead of warning about them.
+            pub strict: bool,
+            /// Write repeated scheme strings once as a YAML anchor and refer to them by alias.
+            pub use_anchors: bool,
+            /// Serialization of the written entries.
+            pub output_format: OutputFormat,
+            /// Only records matching this expression are converted.
+            pub record_filter: Option<RecordFilter>,
+            /// Line ending of the written YAML.
+            pub output_newline: Newline,
+            /// Id of a record whose transformation steps are logged at debug level.
+            pub trace_record: Option<String>,
+            /// Tags attached to every entry.
+            pub tags: Vec<String>,
+            /// Also tag affiliation entries with their record's lowercased `types`.
+            pub tags_from_types: bool,
+            /// Maximum length, in characters, of emitted descriptions.
+            pub max_description_length: Option<usize>,
+        }
+
+        /// Default for `ConvertOptions::max_acronym_length`; generous enough for real acronyms.
+        pub const DEFAULT_MAX_ACRONYM_LENGTH: usize = 30;
+
+        /// Language of the title taken from the record's primary name.
+        pub const PRIMARY_LANGUAGE: &str = "en";
+
+        /// Default for `ConvertOptions::identifier_key`, matching the `YamlEntry` field name.
+        pub const DEFAULT_IDENTIFIER_KEY: &str = "identifiers";
+
+        impl Default for ConvertOptions {
+            fn default() -> Self {
+                ConvertOptions {
+                    input_format: InputFormat::default(),
+                    delimiter: ',',
+                    input_shape: InputShape::default(),
+                    records_path: None,
+                    sqlite_query: None,
+                    strip_html: false,
+                    id_scheme: None,
+                    preserve_scheme_case: false,
+                    on_empty_name: EmptyNamePolicy::default(),
+                    name_from_id_fallback: false,
+                    split_acronyms: false,
+                    redact: Vec::new(),
+                    name_format: NameFormat::default(),
+                    extra_titles: Vec::new(),
+                    lang_allowlist: None,
+                    dedupe_titles: false,
+                    collapse_dialects: false,
+                    create_dirs: false,
+                    tee: None,
+                    included_labels: HashMap::new(),
+                    overrides: HashMap::new(),
+                    lenient: false,
+                    fields: FieldProjection::default(),
+                    respect_preferred: false,
+                    verbosity: Verbosity::default(),
+                    strict_fields: false,
+                    compact_yaml: false,
+                    acronym_strategy: AcronymStrategy::default(),
+                    acronym_pick: AcronymPick::default(),
+                    max_acronym_length: DEFAULT_MAX_ACRONYM_LENGTH,
+                    alphabetize_acronyms: false,
+                    skos: false,
+                    emit_country_name: false,
+                    strip_legal_suffixes: false,
+                    identifier_key: DEFAULT_IDENTIFIER_KEY.to_string(),
+                    full_identifier: false,
+                    identifier_base: None,
+                    entry_id_prefix: None,
+                    vocab_version: None,
+                    version_in_props: false,
+                    verify_output: false,
+                    output_mode: None,
+                    generate_ids: false,
+                    seed: None,
+                    deunicode_errors: DeunicodeErrors::default(),
+                    fold_diacritics_only: false,
+                    strict_utf8: false,
+                    strict: false,
+                    use_anchors: false,
+                    output_format: OutputFormat::default(),
+                    record_filter: None,
+                    output_newline: Newline::default(),
+                    trace_record: None,
+                    tags: Vec::new(),
+                    tags_from_types: false,
+                    max_description_length: None,
+                }
+            }
+        }
+
+        impl ConvertOptions {
+            /// The identifier value to emit for a record whose sanitized id is `full_id`
+            /// and whose last path segment is `segment`.
+            pub fn identifier_value(&self, full_id: &str, segment: &str) -> String {
+                match &self.identifier_base {
+                    Some(base) => format!("{}/{}", base.trim_end_matches('/'), segment),
+                    None if self.full_identifier => full_id.to_string(),
+                    None => segment.to_string(),
+                }
+            }
+
+            /// The entry `id` to emit for `id`, namespaced by `entry_id_prefix`. Empty ids stay empty.
+            pub fn entry_id(&self, id: String) -> String {
+                match &self.entry_id_prefix {
+                    Some(prefix) if !id.is_empty() => format!("{}{}", prefix, id),
+                    _ => id,
+                }
+            }
+
+            /// The supplementary titles for a record whose sanitized id ends in `segment`, if any.
+            pub fn included_labels_for(&self, segment: &str) -> impl Iterator<Item = (&String, &String)> {
+                self.included_labels.get(segment).into_iter().flatten()
+            }
+
+            /// Whether a title in `lang` survives `lang_allowlist`. The primary `en` title always does.
+            pub fn keeps_language(&self, lang: &str) -> bool {
+                lang == PRIMARY_LANGUAGE
+                    || self.lang_allowlist.as_ref().is_none_or(|allowed| allowed.iter().any(|l| l == lang))
+            }
+
+            /// Tags of an entry: the constant `tags`, then, with `tags_from_types`, the record's
+            /// lowercased `types`, without repeats.
+            pub fn entry_tags(&self, types: &[String]) -> Vec<String> {
+                let derived = types.iter().filter(|_| self.tags_from_types).map(|kind| self.clean(kind.trim()).to_lowercase());
+                let mut tags: Vec<String> = Vec::new();
+                for tag in self.tags.iter().cloned().chain(derived) {
+                    if !tag.is_empty() && !tags.contains(&tag) {
+                        tags.push(tag);
+                    }
+                }
+                tags
+            }
+
+            /// The sanitized `description` to emit, capped at `max_description_length` characters.
+            pub fn description(&self, description: &str) -> Option<String> {
+                let description = self.clean(description.trim());
+                let description = match self.max_description_length {
+                    Some(max) if description.chars().count() > max => {
+                        description.chars().take(max).collect::<String>().trim_end().to_string()
+                    }
+                    _ => description,
+                };
+                Some(description).filter(|description| !description.is_empty())
+            }
+
+            /// Whether `--trace-record` selects the record with id segment `id`.
+            pub fn traces(&self, id: &str) -> bool {
+                self.trace_record.as_deref().is_some_and(|traced| !id.is_empty() && traced.rsplit('/').next() == Some(id))
+            }
+
+            /// Whether a title survives `lang_allowlist` and, with `dedupe_titles`, is not a
+            /// non-primary copy of the `primary` title.
+            pub fn keeps_title(&self, lang: &str, value: &str, primary: Option<&str>) -> bool {
+                self.keeps_language(lang)
+                    && (lang == PRIMARY_LANGUAGE || !self.dedupe_titles || primary != Some(value))
+            }
+
+            /// The title key for label language `lang`: with `collapse_dialects`, its base
+            /// language (`pt` for `pt-BR` or `pt_PT`).
+            pub fn title_language<'a>(&self, lang: &'a str) -> &'a str {
+                if self.collapse_dialects {
+                    lang.split(['-', '_']).next().unwrap_or(lang)
+                } else {
+                    lang
+                }
+            }
+
+            /// Whether entries are written as reshaped mappings rather than serialized directly.
+            pub fn reshapes_entries(&self) -> bool {
+                self.fields != FieldProjection::All || self.identifier_key != DEFAULT_IDENTIFIER_KEY
+            }
+
+            /// Whether `acronym` is non-empty and within `max_acronym_length` characters.
+            pub fn accepts_acronym(&self, acronym: &str) -> bool {
+                !acronym.is_empty() && acronym.chars().count() <= self.max_acronym_length
+            }
+
+            /// Print `message` to stderr when the configured verbosity includes `level`.
+            pub fn log(&self, level: Verbosity, message: &str) {
+                if self.verbosity >= level {
+                    eprintln!("{}", message);
+                }
+            }
+
+            /// The identifier scheme to emit for `vocab_type`.
+            ///
+            /// Schemes are lowercased unless `preserve_scheme_case` is set, since InvenioRDM
+            /// matches them case-sensitively.
+            pub fn scheme_for(&self, vocab_type: VocabType) -> String {
+                self.scheme_case(self.id_scheme.as_deref().unwrap_or_else(|| vocab_type.default_scheme()))
+            }
+
+            /// `scheme` lowercased, unless `preserve_scheme_case` is set.
+            pub fn scheme_case(&self, scheme: &str) -> String {
+                if self.preserve_scheme_case {
+                    scheme.to_string()
+                } else {
+                    scheme.to_lowercase()
+                }
+            }
+
+            /// Apply the configured cleaning steps followed by `sanitize`.
+            pub fn clean(&self, s: &str) -> String {
+                if self.strip_html {
+                    self.transliterate(&strip_html(s))
+                } else {
+                    self.transliterate(s)
+                }
+            }
+
+            /// Transliterate to ASCII, handling unmappable characters per `deunicode_errors`.
+            fn transliterate(&self, s: &str) -> String {
+                if self.fold_diacritics_only {
+                    return fold_diacritics(s);
+                }
+                match self.deunicode_errors {
+                    DeunicodeErrors::Strip => deunicode_with_tofu(s, ""),
+                    DeunicodeErrors::Placeholder | DeunicodeErrors::Error => sanitize(s),
+                }
+            }
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct YamlEntry {
+            pub id: String,
+            pub name: String,
+            pub title: HashMap<String, String>,
+            pub identifiers: Vec<Identifier>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub acronym: Option<String>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub acronyms: Vec<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub country: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub country_name: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub url: Option<String>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub tags: Vec<String>,
+            #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+            pub props: BTreeMap<String, String>,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct Identifier {
+            pub identifier: String,
+            pub scheme: String,
+        }
+
+        /// Convert a JSON file containing Affiliations data into a YAML file.
+        /// This function sanitizes all strings to replace ambiguous characters.
+        pub fn convert_json_to_yaml(json_path: &Path, yaml_path: &Path) -> Result<(), Box<dyn Error>> {
+            convert_json_to_yaml_with_options(json_path, yaml_path, &ConvertOptions::default()).map(|_| ())
+        }
+
+        /// Convert a JSON file containing Affiliations data into a YAML file, applying `options`.
+        ///
+        /// The returned report carries the counts and every warning noticed while converting.
+        pub fn convert_json_to_yaml_with_options(
+            json_path: &Path,
+            yaml_path: &Path,
+            options: &ConvertOptions,
+        ) -> Result<ConversionReport, Box<dyn Error>> {
+            let reader = BufReader::new(File::open(json_path)?);
+            let report = convert_json_to_writer(reader, open_output(yaml_path, options)?, options)?;
+            apply_output_modes(yaml_path, options)?;
+
+            if options.verify_output {
+                verify_output(yaml_path, report.emitted_total, options)?;
+            }
+
+            Ok(report)
+        }
+
+        /// Convert Affiliations data read from `reader` and stream the YAML to `writer`.
+        ///
+        /// The input is parsed according to `options.input_format`, so any `Read` source works,
+        /// and the output can be captured in memory or sent over a socket.
+        pub fn convert_json_to_writer(
+            reader: impl std::io::Read,
+            writer: impl Write,
+            options: &ConvertOptions,
+        ) -> Result<ConversionReport, Box<dyn Error>> {
+            let started = std::time::Instant::now();
+            let items = read_items_from(reader, options)?;
+            let (yaml_data, warnings) = convert_items_with_warnings(&items, options)?;
+            write_entries(writer, &yaml_data, options)?;
+            Ok(ConversionReport {
+                records_total: items.len(),
+                emitted_total: yaml_data.len(),
+                skipped_total: items.len() - yaml_data.len(),
+                duration: started.elapsed(),
+                warnings,
+            })
+        }
+
+        /// Read a JSON file containing Affiliations data and convert it into YAML entries
+        /// without writing any output.
+        pub fn convert_json_to_entries(
+            json_path: &Path,
+            options: &ConvertOptions,
+        ) -> Result<Vec<YamlEntry>, Box<dyn Error>> {
+            let items = read_items(json_path, options)?;
+            convert_items(&items, options)
+        }
+
+        /// Read the input file into `AffiliationItem`s according to `options.input_format`.
+        pub fn read_items(input_path: &Path, options: &ConvertOptions) -> Result<Vec<AffiliationItem>, Box<dyn Error>> {
+            let file = File::open(input_path)?;
+            read_items_from(BufReader::new(file), options)
+        }
+
+        /// Read `AffiliationItem`s from any reader according to `options.input_format`.
+        ///
+        /// Gzip-compressed input in any format is decompressed first.
+        pub fn read_items_from<R: Read>(reader: R, options: &ConvertOptions) -> Result<Vec<AffiliationItem>, Box<dyn Error>> {
+            let mut items = super::gzip::with_decompressed(reader, |reader| read_uncompressed_items(reader, options))?;
+            if options.split_acronyms {
+                for item in &mut items {
+                    item.acronyms = split_acronyms(&item.acronyms);
+                }
+            }
+            Ok(items)
+        }
+
+        /// Count the entries `convert_items` would emit for the records in `reader`, without
+        /// converting them.
+        ///
+        /// Records failing `options.record_filter` and, under `--on-empty-name skip`, records with
+        /// an empty name are not counted. A plain JSON array is streamed one record at a time;
+        /// other inputs are read whole first.
+        pub fn count_items_from<R: Read>(reader: R, options: &ConvertOptions) -> Result<usize, Box<dyn Error>> {
+            let mut count = 0;
+            let mut index = 0;
+            let mut tally = |item: AffiliationItem| -> Result<(), Box<dyn Error>> {
+                if emits_entry(index, &item, options)? {
+                    count += 1;
+                }
+                index += 1;
+                Ok(())
+            };
+
+            let streamable = options.input_format == InputFormat::Json
+                && options.input_shape == InputShape::Array
+                && options.records_path.is_none()
+                && !options.strict_utf8;
+            if streamable {
+                super::gzip::with_decompressed(reader, |reader| {
+                    if options.strict_fields {
+                        super::stream_records(reader, options.lenient, &mut |item: StrictAffiliationItem| tally(item.into()))
+                    } else {
+                        super::stream_records(reader, options.lenient, &mut tally)
+                    }
+                })?;
+            } else {
+                for item in read_items_from(reader, options)? {
+                    tally(item)?;
+                }
+            }
+            Ok(count)
+        }
+
+        /// Whether `convert_items` emits an entry for `item`, the record at `index`.
+        ///
+        /// Fails like the conversion does for an empty name under `--on-empty-name error`.
+        fn emits_entry(index: usize, item: &AffiliationItem, options: &ConvertOptions) -> Result<bool, Box<dyn Error>> {
+            if options.record_filter.as_ref().is_some_and(|filter| !filter.matches(item)) {
+                return Ok(false);
+            }
+            if options.on_empty_name == EmptyNamePolicy::Keep || !has_empty_name(item, options) {
+                return Ok(true);
+            }
+            match options.on_empty_name {
+                EmptyNamePolicy::Error => Err(ValidationError(format!("Record {} (id '{}') has an empty name", index, item.id)).into()),
+                _ => Ok(false),
+            }
+        }
+
+        fn read_uncompressed_items(reader: &mut dyn Read, options: &ConvertOptions) -> Result<Vec<AffiliationItem>, Box<dyn Error>> {
+            match options.input_format {
+                InputFormat::Auto => {
+                    // Sniff from the buffered prefix; reading on from the buffer replays it.
+                    let mut reader = BufReader::with_capacity(SNIFF_BYTES, reader);
+                    let peeked = reader.fill_buf()?;
+                    // Documents that wrap their records are always JSON.
+                    let input_format = if options.input_shape == InputShape::Map || options.records_path.is_some() {
+                        InputFormat::Json
+                    } else {
+                        detect_input_format(peeked)
+                    };
+                    options.log(Verbosity::Debug, &format!("Detected input format: {:?}", input_format));
+                    // A header row separated by tabs alone is TSV.
+                    let header = peeked.split(|&b| b == b'\n').find(|line| !line.trim_ascii().is_empty()).unwrap_or_default();
+                    let delimiter = if input_format == InputFormat::Csv && !header.contains(&b',') && header.contains(&b'\t') {
+                        '\t'
+                    } else {
+                        options.delimiter
+                    };
+                    let options = ConvertOptions { input_format, delimiter, ..options.clone() };
+                    read_uncompressed_items(&mut reader, &options)
+                }
+                InputFormat::Json => read_json_array(reader, options),
+                InputFormat::JsonLines => {
+                    if options.strict_fields {
+                        read_json_lines::<StrictAffiliationItem>(reader, options)
+                    } else {
+                        read_json_lines::<AffiliationItem>(reader, options)
+                    }
+                }
+                InputFormat::Csv => {
+                    let mut bytes = Vec::new();
+                    reader.read_to_end(&mut bytes)?;
+                    if options.strict_utf8 {
+                        check_utf8(&bytes)?;
+                    }
+                    let text = String::from_utf8(bytes)
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+                    items_from_delimited(&text, options.delimiter)
+                }
+                InputFormat::Parquet => {
+                    let mut bytes = Vec::new();
+                    reader.read_to_end(&mut bytes)?;
+                    if !bytes.starts_with(PARQUET_MAGIC) {
+                        return Err(ParseError("Invalid Parquet input: the file does not start with the PAR1 magic".to_string()).into());
+                    }
+                    items_from_parquet(bytes).map_err(|err| ParseError(format!("Invalid Parquet input: {}", err)).into())
+                }
+                InputFormat::Sqlite => {
+                    let mut bytes = Vec::new();
+                    reader.read_to_end(&mut bytes)?;
+                    if !bytes.starts_with(SQLITE_MAGIC) {
+                        return Err(ParseError("Invalid SQLite input: the file does not start with the SQLite header".to_string()).into());
+                    }
+                    let query = options.sqlite_query.as_deref().unwrap_or(DEFAULT_SQLITE_QUERY);
+                    items_from_sqlite(&bytes, query).map_err(|err| ParseError(format!("Invalid SQLite input: {}", err)).into())
+                }
+            }
+        }
+
+        /// Guess the format of `bytes`, the start of the input, from its content.
+        ///
+        /// The Parquet magic and the SQLite header identify those files. Otherwise a leading
+        /// `[` means a JSON array and a `{` leading every one of at least two non-empty lines
+        /// means JSON Lines. A first line without either but holding a comma or tab is a
+        /// delimited header row. Anything else, including a single JSON object, is read as JSON.
+        pub fn detect_input_format(bytes: &[u8]) -> InputFormat {
+            if bytes.starts_with(PARQUET_MAGIC) {
+                return InputFormat::Parquet;
+            }
+            if bytes.starts_with(SQLITE_MAGIC) {
+                return InputFormat::Sqlite;
+            }
+            let text = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+            let mut lines = text.split(|&b| b == b'\n').map(<[u8]>::trim_ascii).filter(|line| !line.is_empty());
+            let Some(first) = lines.next() else {
+                return InputFormat::Json;
+            };
+            match first[0] {
+                b'[' => InputFormat::Json,
+                b'{' if first.ends_with(b"}")
+                    && lines.next().is_some_and(|second| second.starts_with(b"{"))
+                    && lines.all(|line| line.starts_with(b"{")) =>
+                {
+                    InputFormat::JsonLines
+                }
+                b'{' => InputFormat::Json,
+                _ if first.contains(&b',') || first.contains(&b'\t') => InputFormat::Csv,
+                _ => InputFormat::Json,
+            }
+        }
+
+        /// Deserialize one record from each non-empty line, reporting failures by line number.
+        fn read_json_lines<T>(reader: &mut dyn Read, options: &ConvertOptions) -> Result<Vec<AffiliationItem>, Box<dyn Error>>
+        where
+            T: serde::de::DeserializeOwned + Into<AffiliationItem>,
+        {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            if options.strict_utf8 {
+                check_utf8(&bytes)?;
+            }
+            let text = String::from_utf8(bytes).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            let text = text.strip_prefix('\u{FEFF}').unwrap_or(&text);
+
+            let mut items = Vec::new();
+            for (index, line) in text.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let item: T = serde_json::from_str(line)
+                    .map_err(|err| ParseError(format!("Invalid JSON record on line {}: {}", index + 1, err)))?;
+                items.push(item.into());
+            }
+            Ok(items)
+        }
+
+        /// Magic number every Parquet file starts (and ends) with.
+        const PARQUET_MAGIC: &[u8; 4] = b"PAR1";
+        /// Header every SQLite 3 database file starts with.
+        const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+        /// How much of the input `--input-format auto` looks at to detect its format.
+        const SNIFF_BYTES: usize = 64 * 1024;
+
+        /// Query selecting the `AffiliationItem` columns when `--query` is not given.
+        pub const DEFAULT_SQLITE_QUERY: &str = "SELECT id, name, country_code, acronyms FROM affiliations";
+
+        /// Split acronym strings that join several acronyms with commas or semicolons,
+        /// e.g. `"MIT, M.I.T."` becomes `"MIT"` and `"M.I.T."`.
+        pub fn split_acronyms(acronyms: &[String]) -> Vec<String> {
+            acronyms
+                .iter()
+                .flat_map(|acronym| acronym.split([',', ';']))
+                .map(|acronym| acronym.trim().to_string())
+                .filter(|acronym| !acronym.is_empty())
+                .collect()
+        }
+
+        /// Deserialize the leading JSON array from `reader`.
+        ///
+        /// Anything other than whitespace after the array is reported with its byte offset,
+        /// unless `options.lenient` is set, in which case it is ignored with a warning.
+        pub fn read_json_array<R: std::io::Read>(reader: R, options: &ConvertOptions) -> Result<Vec<AffiliationItem>, Box<dyn Error>> {
+            if options.strict_fields {
+                read_records::<R, StrictAffiliationItem>(reader, options)
+            } else {
+                read_records::<R, AffiliationItem>(reader, options)
+            }
+        }
+
+        /// Stream-validate a JSON array of affiliation records, honouring `options.strict_fields`.
+        pub fn validate_stream<R: Read>(reader: R, options: &ConvertOptions, max_errors: usize) -> Vec<super::InvalidRecord> {
+            if options.strict_fields {
+                super::validate_stream::<StrictAffiliationItem, R>(reader, max_errors)
+            } else {
+                super::validate_stream::<AffiliationItem, R>(reader, max_errors)
+            }
+        }
+
+        /// Deserialize records of type `T` in the configured input shape.
+        fn read_records<R, T>(reader: R, options: &ConvertOptions) -> Result<Vec<AffiliationItem>, Box<dyn Error>>
+        where
+            R: std::io::Read,
+            T: serde::de::DeserializeOwned + Into<AffiliationItem>,
+        {
+            match options.input_shape {
+                InputShape::Array => {
+                    let items: Vec<T> = read_json_document(reader, options)?;
+                    Ok(items.into_iter().map(Into::into).collect())
+                }
+                InputShape::Map => {
+                    let KeyedRecords(records) = read_json_document::<R, KeyedRecords<T>>(reader, options)?;
+                    Ok(records
+                        .into_iter()
+                        .map(|(key, item)| {
+                            let mut item: AffiliationItem = item.into();
+                            // Fold the map key into the record when it carries no id of its own.
+                            if item.id.is_empty() {
+                                item.id = key;
+                            }
+                            item
+                        })
+                        .collect())
+                }
+            }
+        }
+
+        /// A JSON object of records keyed by id, kept in document order.
+        struct KeyedRecords<T>(Vec<(String, T)>);
+
+        impl<'de, T: Deserialize<'de>> Deserialize<'de> for KeyedRecords<T> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct KeyedVisitor<T>(std::marker::PhantomData<T>);
+
+                impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for KeyedVisitor<T> {
+                    type Value = KeyedRecords<T>;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("an object of records keyed by id")
+                    }
+
+                    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                        let mut records = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                        while let Some(entry) = map.next_entry()? {
+                            records.push(entry);
+                        }
+                        Ok(KeyedRecords(records))
+                    }
+                }
+
+                deserializer.deserialize_map(KeyedVisitor(std::marker::PhantomData))
+            }
+        }
+
+        pub(crate) fn read_json_document<R, T>(mut reader: R, options: &ConvertOptions) -> Result<T, Box<dyn Error>>
+        where
+            R: std::io::Read,
+            T: serde::de::DeserializeOwned,
+        {
+            if options.strict_utf8 {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                check_utf8(&bytes)?;
+                let options = ConvertOptions { strict_utf8: false, ..options.clone() };
+                return read_json_document(bytes.as_slice(), &options);
+            }
+            if let Some(path) = &options.records_path {
+                let document: serde_json::Value = read_first_document(reader, options)?;
+                let records = navigate_records_path(document, path)?;
+                return Ok(serde_json::from_value(records)?);
+            }
+            read_first_document(reader, options)
+        }
+
+        /// Deserialize the first JSON document from `reader`, rejecting trailing data unless lenient.
+        fn read_first_document<R, T>(reader: R, options: &ConvertOptions) -> Result<T, Box<dyn Error>>
+        where
+            R: std::io::Read,
+            T: serde::de::DeserializeOwned,
+        {
+            let mut stream = serde_json::Deserializer::from_reader(reader).into_iter::<T>();
+            let items = match stream.next() {
+                Some(items) => items?,
+                None => return Err(ParseError("Input contains no JSON document".to_string()).into()),
+            };
+
+            let offset = stream.byte_offset();
+            if stream.next().is_some() {
+                if !options.lenient {
+                    return Err(ParseError(format!("Trailing data after the JSON array at byte offset {}", offset)).into());
+                }
+                options.log(
+                    Verbosity::Normal,
+                    &format!("Warning: ignoring trailing data after the JSON array at byte offset {}", offset),
+                );
+            }
+
+            Ok(items)
+        }
+
+        /// Fail with the byte offset and line of the first invalid UTF-8 sequence in `bytes`.
+        pub fn check_utf8(bytes: &[u8]) -> Result<(), ParseError> {
+            let Err(err) = std::str::from_utf8(bytes) else {
+                return Ok(());
+            };
+            let offset = err.valid_up_to();
+            let line = bytes[..offset].iter().filter(|b| **b == b'\n').count() + 1;
+            let invalid = &bytes[offset..offset + err.error_len().unwrap_or(bytes.len() - offset)];
+            let hex: Vec<String> = invalid.iter().map(|b| format!("{:02X}", b)).collect();
+            Err(ParseError(format!(
+                "Invalid UTF-8 at byte offset {} (line {}): unexpected byte sequence {}",
+                offset,
+                line,
+                hex.join(" ")
+            )))
+        }
+
+        /// Descend into `document` along a dotted `path` of object keys or array indices.
+        pub fn navigate_records_path(document: serde_json::Value, path: &str) -> Result<serde_json::Value, Box<dyn Error>> {
+            let mut node = document;
+            for segment in path.split('.').filter(|s| !s.is_empty()) {
+                let next = match &mut node {
+                    serde_json::Value::Object(map) => map.remove(segment),
+                    serde_json::Value::Array(items) => segment
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|index| *index < items.len())
+                        .map(|index| items.swap_remove(index)),
+                    _ => None,
+                };
+                node = next.ok_or_else(|| ParseError(format!("Records path '{}' not found: no element '{}'", path, segment)))?;
+            }
+            Ok(node)
+        }
+
+        /// Build `AffiliationItem`s from delimited text with a header row.
+        ///
+        /// Recognized columns are `id`, `name`, `acronyms` (separated by `|`) and
+        /// `label_<lang>` (one column per label language). Other columns are ignored.
+        pub fn items_from_delimited(text: &str, delimiter: char) -> Result<Vec<AffiliationItem>, Box<dyn Error>> {
+            let mut rows = parse_delimited(text, delimiter)?.into_iter();
+            let header = match rows.next() {
+                Some(header) => header,
+                None => return Ok(Vec::new()),
+            };
+
+            Ok(rows.map(|row| item_from_row(header.iter().map(String::as_str).zip(row))).collect())
+        }
+
+        /// Build an `AffiliationItem` from the named columns of a tabular row (CSV, SQLite).
+        ///
+        /// The `id`, `name`, `country_code`, `acronyms` (separated by `|`) and `label_<lang>`
+        /// columns are read; other columns are ignored.
+        fn item_from_row<'a>(columns: impl Iterator<Item = (&'a str, String)>) -> AffiliationItem {
+            let mut item = AffiliationItem::default();
+            for (column, value) in columns {
+                match column.trim() {
+                    "id" => item.id = value,
+                    "name" => item.name = value,
+                    "country_code" => item.country_code = value,
+                    "acronyms" => {
+                        item.acronyms = value
+                            .split('|')
+                            .map(|s| s.trim().to_string())
+                            .filter(|s| !s.is_empty())
+                            .collect();
+                    }
+                    column => {
+                        if let Some(lang) = column.strip_prefix("label_")
+                            && !value.is_empty()
+                        {
+                            item.labels.push(Label { iso639: lang.to_string(), label: value, preferred: false });
+                        }
+                    }
+                }
+            }
+            item
+        }
+
+        /// Map each row of the Parquet file `bytes` onto an `AffiliationItem` by column name, as for
+        /// CSV input. List columns (such as `acronyms`) are joined with `|`; nulls read as empty.
+        fn items_from_parquet(bytes: Vec<u8>) -> parquet::errors::Result<Vec<AffiliationItem>> {
+            use parquet::file::reader::{FileReader, SerializedFileReader};
+            use parquet::record::Field;
+
+            fn text(field: &Field) -> String {
+                match field {
+                    Field::Null => String::new(),
+                    Field::Str(value) => value.clone(),
+                    Field::Bytes(value) => String::from_utf8_lossy(value.data()).into_owned(),
+                    Field::ListInternal(list) => list.elements().iter().map(text).collect::<Vec<_>>().join("|"),
+                    field => field.to_string(),
+                }
+            }
+
+            let reader = SerializedFileReader::new(bytes::Bytes::from(bytes))?;
+            let mut items = Vec::new();
+            for row in reader.get_row_iter(None)? {
+                let row = row?;
+                items.push(item_from_row(row.get_column_iter().map(|(column, field)| (column.as_str(), text(field)))));
+            }
+            Ok(items)
+        }
+
+        /// Run `query` against the SQLite database image `bytes` and map each result row onto
+        /// an `AffiliationItem` by column name, as for CSV input. `NULL` reads as empty.
+        fn items_from_sqlite(bytes: &[u8], query: &str) -> rusqlite::Result<Vec<AffiliationItem>> {
+            use rusqlite::types::ValueRef;
+
+            let mut connection = rusqlite::Connection::open_in_memory()?;
+            connection.deserialize_read_exact(rusqlite::MAIN_DB, bytes, bytes.len(), true)?;
+            let mut statement = connection.prepare(query)?;
+            let columns: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+            let rows = statement.query_map([], |row| {
+                let mut values = Vec::with_capacity(columns.len());
+                for index in 0..columns.len() {
+                    values.push(match row.get_ref(index)? {
+                        ValueRef::Null => String::new(),
+                        ValueRef::Integer(value) => value.to_string(),
+                        ValueRef::Real(value) => value.to_string(),
+                        ValueRef::Text(text) | ValueRef::Blob(text) => String::from_utf8_lossy(text).into_owned(),
+                    });
+                }
+                Ok(item_from_row(columns.iter().map(String::as_str).zip(values)))
+            })?;
+            rows.collect()
+        }
+
+        /// Split delimited text into rows of fields, honouring double-quoted fields.
+        fn parse_delimited(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+            let mut rows = Vec::new();
+            let mut row = Vec::new();
+            let mut field = String::new();
+            let mut in_quotes = false;
+            let mut chars = text.trim_start_matches('\u{FEFF}').chars().peekable();
+
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    match c {
+                        '"' if chars.peek() == Some(&'"') => {
+                            field.push('"');
+                            chars.next();
+                        }
+                        '"' => in_quotes = false,
+                        _ => field.push(c),
+                    }
+                } else if c == '"' && field.is_empty() {
+                    in_quotes = true;
+                } else if c == delimiter {
+                    row.push(std::mem::take(&mut field));
+                } else if c == '\n' || c == '\r' {
+                    if c == '\r' && chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    row.push(std::mem::take(&mut field));
+                    if row.iter().any(|f| !f.is_empty()) {
+                        rows.push(std::mem::take(&mut row));
+                    } else {
+                        row.clear();
+                    }
+                } else {
+                    field.push(c);
+                }
+            }
+
+            if in_quotes {
+                return Err(ParseError("Unterminated quoted field in delimited input".to_string()).into());
+            }
+            row.push(field);
+            if row.iter().any(|f| !f.is_empty()) {
+                rows.push(row);
+            }
+
+            Ok(rows)
+        }
+
+        /// Convert parsed `AffiliationItem`s into YAML entries, applying record-level policies.
+        pub fn convert_items(
+            items: &[AffiliationItem],
+            options: &ConvertOptions,
+        ) -> Result<Vec<YamlEntry>, Box<dyn Error>> {
+            convert_items_with_warnings(items, options).map(|(entries, _)| entries)
+        }
+
+        /// Like `convert_items`, but also return the warnings noticed along the way.
+        ///
+        /// Warnings are still logged as they occur; the returned list lets embedders act on them.
+        pub fn convert_items_with_warnings(
+            items: &[AffiliationItem],
+            options: &ConvertOptions,
+        ) -> Result<(Vec<YamlEntry>, Vec<Warning>), Box<dyn Error>> {
+            convert_items_with_records(items, options).map(|(entries, _, warnings)| (entries, warnings))
+        }
+
+        /// Converted entries, the input record index of each entry, and the warnings noticed.
+        pub type RecordedEntries = (Vec<YamlEntry>, Vec<usize>, Vec<Warning>);
+
+        /// Like `convert_items_with_warnings`, but also return the input record index of each entry.
+        pub fn convert_items_with_records(items: &[AffiliationItem], options: &ConvertOptions) -> Result<RecordedEntries, Box<dyn Error>> {
+            let mut yaml_data = Vec::with_capacity(items.len());
+            let mut records = Vec::with_capacity(items.len());
+            let mut warnings = Vec::new();
+            let mut seen_ids = std::collections::HashSet::with_capacity(items.len());
+
+            let generated_ids = if options.generate_ids { generate_ids(items, options) } else { Vec::new() };
+
+            let mut warn = |level: Verbosity, kind: WarningKind, index: usize, item: &AffiliationItem, message: String| {
+                options.log(level, &message);
+                warnings.push(Warning { kind, record: index, id: item.id.clone(), message });
+            };
+
+            for (index, item) in items.iter().enumerate() {
+                if options.record_filter.as_ref().is_some_and(|filter| !filter.matches(item)) {
+                    warn(
+                        Verbosity::Verbose,
+                        WarningKind::SkippedRecord,
+                        index,
+                        item,
+                        format!("Skipping record {} (id '{}'): does not match the record filter", index, item.id),
+                    );
+                    continue;
+                }
+
+                if options.deunicode_errors == DeunicodeErrors::Error && !options.fold_diacritics_only {
+                    check_transliterable(index, item)?;
+                }
+
+                for label in item.labels.iter().filter(|label| !is_valid_language_code(options.title_language(&label.iso639))) {
+                    warn(
+                        Verbosity::Normal,
+                        WarningKind::InvalidLanguage,
+                        index,
+                        item,
+                        format!("Warning: record {} (id '{}') has an invalid language code '{}'", index, item.id, label.iso639),
+                    );
+                }
+
+                for acronym in item.acronyms.iter().filter(|s| !s.is_empty() && !options.accepts_acronym(s)) {
+                    warn(
+                        Verbosity::Normal,
+                        WarningKind::LongAcronym,
+                        index,
+                        item,
+                        format!(
+                            "Warning: record {} (id '{}') drops acronym '{}' longer than {} characters",
+                            index, item.id, acronym, options.max_acronym_length
+                        ),
+                    );
+                }
+
+                if options.emit_country_name
+                    && !item.country_code.trim().is_empty()
+                    && country_name(&item.country_code.trim().to_ascii_uppercase()).is_none()
+                {
+                    warn(
+                        Verbosity::Normal,
+                        WarningKind::UnknownCountry,
+                        index,
+                        item,
+                        format!("Warning: record {} (id '{}') has an unknown country code '{}'", index, item.id, item.country_code),
+                    );
+                }
+
+                for link in homepage_candidates(item).filter(|url| !is_http_url(url)) {
+                    warn(
+                        Verbosity::Normal,
+                        WarningKind::InvalidUrl,
+                        index,
+                        item,
+                        format!("Warning: record {} (id '{}') has a malformed homepage URL '{}'", index, item.id, link),
+                    );
+                }
+
+                if !item.id.is_empty() && !seen_ids.insert(item.id.as_str()) {
+                    warn(
+                        Verbosity::Normal,
+                        WarningKind::DuplicateId,
+                        index,
+                        item,
+                        format!("Warning: record {} repeats the id '{}'", index, item.id),
+                    );
+                }
+
+                let mut yaml_entry = convert_item(item, options);
+                if let Some(id) = generated_ids.get(index).cloned().flatten() {
+                    options.log(Verbosity::Debug, &format!("Record {} has no id; generated '{}'", index, id));
+                    // A generated id is not a registry identifier, so none is emitted.
+                    yaml_entry.id = options.entry_id(id);
+                    yaml_entry.identifiers.clear();
+                }
+
+                if yaml_entry.name.is_empty() {
+                    match options.on_empty_name {
+                        EmptyNamePolicy::Skip => {
+                            warn(
+                                Verbosity::Verbose,
+                                WarningKind::SkippedRecord,
+                                index,
+                                item,
+                                format!("Skipping record {} (id '{}'): empty name", index, item.id),
+                            );
+                            continue;
+                        }
+                        EmptyNamePolicy::Keep => {}
+                        EmptyNamePolicy::Error => {
+                            return Err(ValidationError(format!("Record {} (id '{}') has an empty name", index, item.id)).into());
+                        }
+                    }
+                }
+
+                yaml_data.push(yaml_entry);
+                records.push(index);
+            }
+
+            // InvenioRDM links entries by identifier value, so each value may only be used once.
+            let duplicates = duplicate_identifiers(&yaml_data);
+            for &(entry, first, identifier) in &duplicates {
+                let (index, item) = (records[entry], &items[records[entry]]);
+                // Entries repeating a record id were already reported as duplicate ids.
+                if item.id != items[records[first]].id {
+                    warn(
+                        Verbosity::Normal,
+                        WarningKind::DuplicateIdentifier,
+                        index,
+                        item,
+                        format!(
+                            "Warning: record {} (id '{}') repeats the identifier '{}' of record {}",
+                            index, item.id, identifier, records[first]
+                        ),
+                    );
+                }
+            }
+            if options.strict && !duplicates.is_empty() {
+                let listed: Vec<String> = duplicates
+                    .iter()
+                    .map(|&(entry, first, identifier)| format!("'{}' (records {} and {})", identifier, records[first], records[entry]))
+                    .collect();
+                return Err(ValidationError(format!("Duplicate identifiers: {}", listed.join(", "))).into());
+            }
+
+            Ok((yaml_data, records, warnings))
+        }
+
+        /// Look the ROR ids of `yaml_data` up in `registry`, warning about those it does not know.
+        ///
+        /// `records` maps each entry to its index in `items`, as returned by `convert_items_with_records`.
+        pub fn compare_with_registry(
+            yaml_data: &[YamlEntry],
+            records: &[usize],
+            items: &[AffiliationItem],
+            registry: &super::registry::Registry,
+            options: &ConvertOptions,
+        ) -> Result<Vec<Warning>, Box<dyn Error>> {
+            // Identifiers may have been emitted as full URLs; the registry is keyed by the bare id.
+            let mut ids = Vec::new();
+            for (entry, yaml_entry) in yaml_data.iter().enumerate() {
+                for identifier in yaml_entry.identifiers.iter().filter(|identifier| identifier.scheme == "ror") {
+                    let id = identifier.identifier.rsplit('/').next().unwrap_or_default();
+                    if !id.is_empty() {
+                        ids.push((id, entry));
+                    }
+                }
+            }
+
+            let values: Vec<&str> = ids.iter().map(|&(id, _)| id).collect();
+            let unknown = registry.unknown_ids(&values)?;
+            let mut warnings = Vec::new();
+            for &(id, entry) in ids.iter().filter(|(id, _)| unknown.contains(id)) {
+                let index = records[entry];
+                let message = format!("Warning: record {} (id '{}') has the ROR id '{}', which the registry does not know", index, items[index].id, id);
+                options.log(Verbosity::Normal, &message);
+                warnings.push(Warning { kind: WarningKind::UnknownRegistryId, record: index, id: items[index].id.clone(), message });
+            }
+            Ok(warnings)
+        }
+
+        /// Every repeated identifier value as `(entry, first entry using it, value)`, in entry order.
+        pub fn duplicate_identifiers(yaml_data: &[YamlEntry]) -> Vec<(usize, usize, &str)> {
+            let mut owners: HashMap<&str, usize> = HashMap::new();
+            let mut duplicates = Vec::new();
+            for (index, entry) in yaml_data.iter().enumerate() {
+                for identifier in entry.identifiers.iter().map(|identifier| identifier.identifier.as_str()) {
+                    if identifier.is_empty() {
+                        continue;
+                    }
+                    let first = *owners.entry(identifier).or_insert(index);
+                    if first != index {
+                        duplicates.push((index, first, identifier));
+                    }
+                }
+            }
+            duplicates
+        }
+
+        /// Whether `code` looks like a BCP 47 (RFC 5646) tag of the form `lang[-script][-region]`:
+        /// an ISO 639-1 or 639-2 code (two or three lowercase letters), optionally followed by a
+        /// four-letter script (`zh-Hant`) and a two-letter or three-digit region (`pt-BR`, `es-419`).
+        /// Empty codes are not reported; such labels are already dropped from the title map.
+        fn is_valid_language_code(code: &str) -> bool {
+            if code.is_empty() {
+                return true;
+            }
+            let mut subtags = code.split('-').peekable();
+            let language = subtags.next().unwrap_or_default();
+            if !((2..=3).contains(&language.len()) && language.bytes().all(|b| b.is_ascii_lowercase())) {
+                return false;
+            }
+            subtags.next_if(|script| script.len() == 4 && script.bytes().all(|b| b.is_ascii_alphabetic()));
+            match subtags.next() {
+                None => true,
+                Some(region) => {
+                    subtags.next().is_none()
+                        && ((region.len() == 2 && region.bytes().all(|b| b.is_ascii_alphabetic()))
+                            || (region.len() == 3 && region.bytes().all(|b| b.is_ascii_digit())))
+                }
+            }
+        }
+
+        /// Generate ids for the records of `items` that have none, indexed like `items`.
+        ///
+        /// Each id is a lowercase slug of the sanitized name. Records are visited in input
+        /// order and a slug already taken (by an input id or an earlier generated one) gets a
+        /// suffix: `-2`, `-3`, ... or, with `options.seed`, a short hash of the seed, slug and
+        /// occurrence. The result depends only on the input and the seed.
+        pub fn generate_ids(items: &[AffiliationItem], options: &ConvertOptions) -> Vec<Option<String>> {
+            let mut taken: std::collections::HashSet<String> = items
+                .iter()
+                .filter(|item| !item.id.is_empty())
+                .map(|item| options.clean(&item.id).split('/').next_back().unwrap_or_default().to_string())
+                .collect();
+
+            items
+                .iter()
+                .map(|item| {
+                    if !item.id.is_empty() {
+                        return None;
+                    }
+                    let mut slug = slugify(&sanitize(&item.name));
+                    if slug.is_empty() {
+                        slug = "record".to_string();
+                    }
+                    let mut candidate = slug.clone();
+                    let mut occurrence: u64 = 1;
+                    while taken.contains(&candidate) {
+                        occurrence += 1;
+                        candidate = match options.seed {
+                            Some(seed) => {
+                                let hash = super::fnv1a(super::FNV_OFFSET_BASIS, &seed.to_le_bytes());
+                                let hash = super::fnv1a(hash, slug.as_bytes());
+                                let hash = super::fnv1a(hash, &occurrence.to_le_bytes());
+                                format!("{}-{:06x}", slug, hash & 0xff_ffff)
+                            }
+                            None => format!("{}-{}", slug, occurrence),
+                        };
+                    }
+                    taken.insert(candidate.clone());
+                    Some(candidate)
+                })
+                .collect()
+        }
+
+        /// Lowercase ASCII slug of `s`: alphanumeric runs joined by single hyphens.
+        pub fn slugify(s: &str) -> String {
+            s.split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .map(str::to_ascii_lowercase)
+                .collect::<Vec<_>>()
+                .join("-")
+        }
+
+        /// Fail if any string field of `item` holds a character `deunicode` cannot map.
+        fn check_transliterable(index: usize, item: &AffiliationItem) -> Result<(), Box<dyn Error>> {
+            let fields = [item.id.as_str(), item.name.as_str()]
+                .into_iter()
+                .chain(item.labels.iter().flat_map(|label| [label.iso639.as_str(), label.label.as_str()]))
+                .chain(item.acronyms.iter().map(String::as_str));
+            for field in fields {
+                if let Some(c) = unmappable_char(field) {
+                    return Err(ValidationError(format!(
+                        "Record {} (id '{}') contains the untransliterable character U+{:04X} in '{}'",
+                        index, item.id, c as u32, field
+                    ))
+                    .into());
+                }
+            }
+            Ok(())
+        }
+
+        /// The display name of `item` before legal suffixes are stripped, reporting each step to `trace`.
+        fn base_name(item: &AffiliationItem, options: &ConvertOptions, id_part: &str, trace: &dyn Fn(std::fmt::Arguments)) -> String {
+            // A preferred label, when honoured, replaces the top-level name for display.
+            let preferred = item.labels.iter()
+                .find(|label| label.preferred && !label.label.is_empty())
+                .filter(|_| options.respect_preferred);
+            let name = match preferred {
+                Some(label) => options.clean(&label.label),
+                None => options.clean(&item.name),
+            };
+            match preferred {
+                Some(label) => trace(format_args!("name from preferred {} label '{}' -> '{}'", label.iso639, label.label, name)),
+                None => trace(format_args!("name '{}' -> '{}'", item.name, name)),
+            }
+
+            // A record with neither a name nor any label can borrow its id segment as a placeholder.
+            if name.is_empty()
+                && options.name_from_id_fallback
+                && item.labels.iter().all(|label| options.clean(&label.label).is_empty())
+            {
+                trace(format_args!("name is empty; using the id segment '{}'", id_part));
+                id_part.to_string()
+            } else {
+                name
+            }
+        }
+
+        /// Whether the entry converted from `item` has an empty name, without converting it.
+        ///
+        /// Stripping legal suffixes never empties a name, so only overrides and `base_name` matter.
+        fn has_empty_name(item: &AffiliationItem, options: &ConvertOptions) -> bool {
+            let id_sanitized = options.clean(&item.id);
+            let id_part = id_sanitized.split('/').next_back().unwrap_or_default();
+            match options.overrides.get(id_part).and_then(|corrections| corrections.name.as_ref()) {
+                Some(name) => options.clean(name).is_empty(),
+                None => base_name(item, options, id_part, &|_| {}).is_empty(),
+            }
+        }
+
+        /// Convert a single `AffiliationItem` into its YAML entry.
+        pub fn convert_item(item: &AffiliationItem, options: &ConvertOptions) -> YamlEntry {
+            // Sanitize the id and extract the last segment.
+            let id_sanitized = options.clean(&item.id);
+            let id_part = id_sanitized.split('/').next_back().unwrap_or_default().to_string();
+
+            // `--trace-record` logs each step for one record; arguments are only formatted when traced.
+            let tracing = options.traces(&id_part);
+            let trace = |step: std::fmt::Arguments| {
+                if tracing {
+                    options.log(Verbosity::Debug, &format!("trace {}: {}", id_part, step));
+                }
+            };
+            trace(format_args!("id '{}' -> '{}'", item.id, id_part));
+
+            let name = base_name(item, options, &id_part, &trace);
+
+            // Pre-size for the primary name, every label and the constant titles to avoid rehashing.
+            let mut title = HashMap::with_capacity(1 + item.labels.len() + options.extra_titles.len());
+            title.insert(PRIMARY_LANGUAGE.to_string(), name.clone());
+
+            // Legal suffixes are only dropped from the display name, never from the titles.
+            let name = if options.strip_legal_suffixes {
+                let stripped = strip_legal_suffix(&name);
+                trace(format_args!("legal suffix stripped: '{}' -> '{}'", name, stripped));
+                stripped
+            } else {
+                name
+            };
+
+            // Process and sanitize any labels.
+            for label in &item.labels {
+                if !label.iso639.is_empty() && !label.label.is_empty() {
+                    let (lang, value) = (options.clean(&label.iso639), options.clean(&label.label));
+                    // The first dialect label fills the base language; a label in the base language itself replaces it.
+                    let base = options.title_language(&lang);
+                    if base != lang && title.contains_key(base) {
+                        trace(format_args!("label {} '{}' skipped: {} title already set", lang, label.label, base));
+                        continue;
+                    }
+                    if base == lang {
+                        trace(format_args!("label {} '{}' -> '{}'", lang, label.label, value));
+                    } else {
+                        trace(format_args!("label {} '{}' -> {} '{}'", lang, label.label, base, value));
+                    }
+                    insert_title(&mut title, base.to_string(), value);
+                } else {
+                    trace(format_args!("label {} '{}' skipped: empty language or label", label.iso639, label.label));
+                }
+            }
+
+            // Inject any constant titles requested by the caller.
+            for (lang, value) in &options.extra_titles {
+                trace(format_args!("constant title {} '{}'", lang, value));
+                title.insert(lang.clone(), options.clean(value));
+            }
+
+            // Local translations win over everything taken from the source record.
+            for (lang, value) in options.included_labels_for(&id_part) {
+                trace(format_args!("included title {} '{}'", lang, value));
+                title.insert(lang.clone(), options.clean(value));
+            }
+
+            let primary = title.get(PRIMARY_LANGUAGE).cloned();
+            title.retain(|lang, value| {
+                let keep = options.keeps_title(lang, value, primary.as_deref());
+                if !keep {
+                    trace(format_args!("title {} '{}' dropped", lang, value));
+                }
+                keep
+            });
+
+            // Pick the primary acronym among the usable ones, if any.
+            let acronym = options
+                .acronym_pick
+                .pick(item.acronyms.iter().filter(|s| options.accepts_acronym(s)).map(|s| options.clean(s)));
+            match &acronym {
+                Some(acronym) => trace(format_args!("acronym '{}' selected from {:?}", acronym, item.acronyms)),
+                None => trace(format_args!("no usable acronym in {:?}", item.acronyms)),
+            }
+
+            // Keep every distinct acronym when the strategy asks for more than the first.
+            let strategy = options.acronym_strategy;
+            let distinct_acronyms = if strategy != AcronymStrategy::First {
+                let mut acronyms =
+                    dedupe_acronyms(item.acronyms.iter().filter(|s| options.accepts_acronym(s)).map(|s| options.clean(s)));
+                if options.alphabetize_acronyms {
+                    acronyms.sort();
+                }
+                acronyms
+            } else {
+                Vec::new()
+            };
+
+            let identifier = Identifier {
+                identifier: options.identifier_value(&id_sanitized, &id_part),
+                scheme: options.scheme_for(VocabType::Affiliations),
+            };
+
+            let country = Some(item.country_code.trim().to_ascii_uppercase()).filter(|code| !code.is_empty());
+            let country_name = country
+                .as_deref()
+                .filter(|_| options.emit_country_name)
+                .and_then(country_name)
+                .map(|name| options.clean(name));
+
+            let url = homepage_candidates(item).find(|url| is_http_url(url)).map(str::to_string);
+
+            let mut props = BTreeMap::new();
+            if let Some(version) = options.vocab_version.as_ref().filter(|_| options.version_in_props) {
+                props.insert("version".to_string(), version.clone());
+            }
+            if strategy.adds_title_variants() && !distinct_acronyms.is_empty() {
+                props.insert("title_variants".to_string(), distinct_acronyms.join("; "));
+            }
+            let acronyms = if strategy.lists_all() { distinct_acronyms } else { Vec::new() };
+
+            // Ids in other registries follow the record's own identifier, schemed by their type.
+            let identifiers = std::iter::once(identifier)
+                .chain(item.external_ids.ids().into_iter().map(|(kind, value)| Identifier {
+                    identifier: options.clean(value),
+                    scheme: options.scheme_case(kind),
+                }))
+                .collect::<Vec<_>>();
+            for identifier in &identifiers {
+                trace(format_args!("identifier {} '{}'", identifier.scheme, identifier.identifier));
+            }
+
+            let mut entry = YamlEntry {
+                id: options.entry_id(id_part.clone()),
+                name,
+                title,
+                identifiers,
+                acronym,
+                acronyms,
+                country,
+                country_name,
+                url,
+                tags: options.entry_tags(&item.types),
+                props,
+            };
+
+            // Manual corrections win over everything derived from the record.
+            if let Some(corrections) = options.overrides.get(&id_part) {
+                trace(format_args!("overrides applied: {:?}", corrections));
+                corrections.apply(&mut entry, options);
+            }
+            entry
+        }
+
+        /// Non-empty homepage URLs of `item` in priority order: website links, then `wikipedia_url`.
+        fn homepage_candidates(item: &AffiliationItem) -> impl Iterator<Item = &str> {
+            item.links
+                .iter()
+                .filter(|link| link.is_website())
+                .map(|link| link.url().trim())
+                .chain(std::iter::once(item.wikipedia_url.trim()))
+                .filter(|url| !url.is_empty())
+        }
+
+        /// Whether `url` is a well-formed absolute http or https URL with a host.
+        pub fn is_http_url(url: &str) -> bool {
+            let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) else {
+                return false;
+            };
+            let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+            !host.is_empty()
+                && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+                && host.chars().all(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+        }
+
+        /// Read supplementary titles from a JSON object mapping record ids to `{ lang: label }` maps.
+        ///
+        /// Ids may be given in full (`https://ror.org/00aaa1234`) or bare; both are keyed by their
+        /// last path segment so they match the emitted id.
+        pub fn read_included_labels(path: &Path) -> Result<HashMap<String, BTreeMap<String, String>>, Box<dyn Error>> {
+            let file = File::open(path)?;
+            let labels: HashMap<String, BTreeMap<String, String>> = serde_json::from_reader(BufReader::new(file))?;
+            Ok(labels
+                .into_iter()
+                .map(|(id, titles)| (id.trim_end_matches('/').rsplit('/').next().unwrap_or_default().to_string(), titles))
+                .collect())
+        }
+
+        /// Manual corrections for one entry, read from an `--overrides` file. Every field given
+        /// replaces the value derived from the source record.
+        #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        pub struct EntryOverride {
+            pub name: Option<String>,
+            pub title: Option<HashMap<String, String>>,
+            pub acronym: Option<String>,
+            pub acronyms: Option<Vec<String>>,
+            pub country: Option<String>,
+            pub country_name: Option<String>,
+            pub url: Option<String>,
+            pub tags: Option<Vec<String>>,
+            pub props: Option<BTreeMap<String, String>>,
+        }
+
+        impl EntryOverride {
+            /// Replace the fields of `entry` this override gives, sanitized like source values.
+            pub fn apply(&self, entry: &mut YamlEntry, options: &ConvertOptions) {
+                let clean = |value: &String| options.clean(value);
+                if let Some(name) = &self.name {
+                    entry.name = clean(name);
+                }
+                if let Some(title) = &self.title {
+                    entry.title = title.iter().map(|(lang, value)| (clean(lang), clean(value))).collect();
+                }
+                if let Some(acronym) = &self.acronym {
+                    entry.acronym = Some(clean(acronym)).filter(|acronym| !acronym.is_empty());
+                }
+                if let Some(acronyms) = &self.acronyms {
+                    entry.acronyms = acronyms.iter().map(clean).collect();
+                }
+                if let Some(country) = &self.country {
+                    entry.country = Some(country.trim().to_ascii_uppercase()).filter(|code| !code.is_empty());
+                    // A corrected country keeps a derived country name in step.
+                    if options.emit_country_name {
+                        entry.country_name = entry.country.as_deref().and_then(country_name).map(|name| options.clean(name));
+                    }
+                }
+                if let Some(name) = &self.country_name {
+                    entry.country_name = Some(clean(name));
+                }
+                if let Some(url) = &self.url {
+                    entry.url = Some(url.trim().to_string()).filter(|url| !url.is_empty());
+                }
+                if let Some(tags) = &self.tags {
+                    entry.tags = tags.iter().map(clean).collect();
+                }
+                if let Some(props) = &self.props {
+                    entry.props = props.iter().map(|(key, value)| (key.clone(), clean(value))).collect();
+                }
+            }
+        }
+
+        /// Read per-entry corrections from a JSON object mapping record ids to `EntryOverride`s.
+        ///
+        /// Ids are keyed by their last path segment, as in `read_included_labels`. Unknown fields
+        /// are rejected so a typo cannot silently leave a correction unapplied.
+        pub fn read_overrides(path: &Path) -> Result<HashMap<String, EntryOverride>, Box<dyn Error>> {
+            let file = File::open(path)?;
+            let overrides: HashMap<String, EntryOverride> = serde_json::from_reader(BufReader::new(file))
+                .map_err(|err| ParseError(format!("Invalid overrides file {}: {}", path.display(), err)))?;
+            Ok(overrides
+                .into_iter()
+                .map(|(id, corrections)| (id.trim_end_matches('/').rsplit('/').next().unwrap_or_default().to_string(), corrections))
+                .collect())
+        }
+
+        /// Insert a title, merging it with an existing one that differs only in whitespace.
+        ///
+        /// Near-duplicates keep the trimmed, whitespace-collapsed form; genuinely different
+        /// titles replace the existing one as before.
+        fn insert_title(title: &mut HashMap<String, String>, lang: String, value: String) {
+            let collapsed = collapse_whitespace(&value);
+            match title.get_mut(&lang) {
+                Some(existing) if collapse_whitespace(existing) == collapsed => *existing = collapsed,
+                _ => {
+                    title.insert(lang, value);
+                }
+            }
+        }
+
+        /// Trim `s` and collapse each run of inner whitespace into a single space.
+        pub fn collapse_whitespace(s: &str) -> String {
+            s.split_whitespace().collect::<Vec<_>>().join(" ")
+        }
+
+        /// Look up the English short name for an ISO 3166-1 alpha-2 country code.
+        pub fn country_name(code: &str) -> Option<&'static str> {
+            COUNTRY_NAMES
+                .binary_search_by(|(candidate, _)| candidate.cmp(&code))
+                .ok()
+                .map(|index| COUNTRY_NAMES[index].1)
+        }
+
+        /// ISO 3166-1 alpha-2 codes and their English short names, sorted by code.
+        const COUNTRY_NAMES: &[(&str, &str)] = &[
+            ("AD", "Andorra"), ("AE", "United Arab Emirates"), ("AF", "Afghanistan"), ("AG", "Antigua and Barbuda"),
+            ("AI", "Anguilla"), ("AL", "Albania"), ("AM", "Armenia"), ("AO", "Angola"), ("AQ", "Antarctica"),
+            ("AR", "Argentina"), ("AS", "American Samoa"), ("AT", "Austria"), ("AU", "Australia"), ("AW", "Aruba"),
+            ("AX", "Åland Islands"), ("AZ", "Azerbaijan"), ("BA", "Bosnia and Herzegovina"), ("BB", "Barbados"),
+            ("BD", "Bangladesh"), ("BE", "Belgium"), ("BF", "Burkina Faso"), ("BG", "Bulgaria"), ("BH", "Bahrain"),
+            ("BI", "Burundi"), ("BJ", "Benin"), ("BL", "Saint Barthélemy"), ("BM", "Bermuda"),
+            ("BN", "Brunei Darussalam"), ("BO", "Bolivia"), ("BQ", "Bonaire, Sint Eustatius and Saba"),
+            ("BR", "Brazil"), ("BS", "Bahamas"), ("BT", "Bhutan"), ("BV", "Bouvet Island"), ("BW", "Botswana"),
+            ("BY", "Belarus"), ("BZ", "Belize"), ("CA", "Canada"), ("CC", "Cocos (Keeling) Islands"),
+            ("CD", "Congo, The Democratic Republic of the"), ("CF", "Central African Republic"), ("CG", "Congo"),
+            ("CH", "Switzerland"), ("CI", "Côte d'Ivoire"), ("CK", "Cook Islands"), ("CL", "Chile"),
+            ("CM", "Cameroon"), ("CN", "China"), ("CO", "Colombia"), ("CR", "Costa Rica"), ("CU", "Cuba"),
+            ("CV", "Cabo Verde"), ("CW", "Curaçao"), ("CX", "Christmas Island"), ("CY", "Cyprus"), ("CZ", "Czechia"),
+            ("DE", "Germany"), ("DJ", "Djibouti"), ("DK", "Denmark"), ("DM", "Dominica"),
+            ("DO", "Dominican Republic"), ("DZ", "Algeria"), ("EC", "Ecuador"), ("EE", "Estonia"), ("EG", "Egypt"),
+            ("EH", "Western Sahara"), ("ER", "Eritrea"), ("ES", "Spain"), ("ET", "Ethiopia"), ("FI", "Finland"),
+            ("FJ", "Fiji"), ("FK", "Falkland Islands (Malvinas)"), ("FM", "Micronesia, Federated States of"),
+            ("FO", "Faroe Islands"), ("FR", "France"), ("GA", "Gabon"), ("GB", "United Kingdom"), ("GD", "Grenada"),
+            ("GE", "Georgia"), ("GF", "French Guiana"), ("GG", "Guernsey"), ("GH", "Ghana"), ("GI", "Gibraltar"),
+            ("GL", "Greenland"), ("GM", "Gambia"), ("GN", "Guinea"), ("GP", "Guadeloupe"),
+            ("GQ", "Equatorial Guinea"), ("GR", "Greece"), ("GS", "South Georgia and the South Sandwich Islands"),
+            ("GT", "Guatemala"), ("GU", "Guam"), ("GW", "Guinea-Bissau"), ("GY", "Guyana"), ("HK", "Hong Kong"),
+            ("HM", "Heard Island and McDonald Islands"), ("HN", "Honduras"), ("HR", "Croatia"), ("HT", "Haiti"),
+            ("HU", "Hungary"), ("ID", "Indonesia"), ("IE", "Ireland"), ("IL", "Israel"), ("IM", "Isle of Man"),
+            ("IN", "India"), ("IO", "British Indian Ocean Territory"), ("IQ", "Iraq"), ("IR", "Iran"),
+            ("IS", "Iceland"), ("IT", "Italy"), ("JE", "Jersey"), ("JM", "Jamaica"), ("JO", "Jordan"),
+            ("JP", "Japan"), ("KE", "Kenya"), ("KG", "Kyrgyzstan"), ("KH", "Cambodia"), ("KI", "Kiribati"),
+            ("KM", "Comoros"), ("KN", "Saint Kitts and Nevis"), ("KP", "North Korea"), ("KR", "South Korea"),
+            ("KW", "Kuwait"), ("KY", "Cayman Islands"), ("KZ", "Kazakhstan"), ("LA", "Laos"), ("LB", "Lebanon"),
+            ("LC", "Saint Lucia"), ("LI", "Liechtenstein"), ("LK", "Sri Lanka"), ("LR", "Liberia"), ("LS", "Lesotho"),
+            ("LT", "Lithuania"), ("LU", "Luxembourg"), ("LV", "Latvia"), ("LY", "Libya"), ("MA", "Morocco"),
+            ("MC", "Monaco"), ("MD", "Moldova"), ("ME", "Montenegro"), ("MF", "Saint Martin (French part)"),
+            ("MG", "Madagascar"), ("MH", "Marshall Islands"), ("MK", "North Macedonia"), ("ML", "Mali"),
+            ("MM", "Myanmar"), ("MN", "Mongolia"), ("MO", "Macao"), ("MP", "Northern Mariana Islands"),
+            ("MQ", "Martinique"), ("MR", "Mauritania"), ("MS", "Montserrat"), ("MT", "Malta"), ("MU", "Mauritius"),
+            ("MV", "Maldives"), ("MW", "Malawi"), ("MX", "Mexico"), ("MY", "Malaysia"), ("MZ", "Mozambique"),
+            ("NA", "Namibia"), ("NC", "New Caledonia"), ("NE", "Niger"), ("NF", "Norfolk Island"), ("NG", "Nigeria"),
+            ("NI", "Nicaragua"), ("NL", "Netherlands"), ("NO", "Norway"), ("NP", "Nepal"), ("NR", "Nauru"),
+            ("NU", "Niue"), ("NZ", "New Zealand"), ("OM", "Oman"), ("PA", "Panama"), ("PE", "Peru"),
+            ("PF", "French Polynesia"), ("PG", "Papua New Guinea"), ("PH", "Philippines"), ("PK", "Pakistan"),
+            ("PL", "Poland"), ("PM", "Saint Pierre and Miquelon"), ("PN", "Pitcairn"), ("PR", "Puerto Rico"),
+            ("PS", "Palestine, State of"), ("PT", "Portugal"), ("PW", "Palau"), ("PY", "Paraguay"), ("QA", "Qatar"),
+            ("RE", "Réunion"), ("RO", "Romania"), ("RS", "Serbia"), ("RU", "Russian Federation"), ("RW", "Rwanda"),
+            ("SA", "Saudi Arabia"), ("SB", "Solomon Islands"), ("SC", "Seychelles"), ("SD", "Sudan"),
+            ("SE", "Sweden"), ("SG", "Singapore"), ("SH", "Saint Helena, Ascension and Tristan da Cunha"),
+            ("SI", "Slovenia"), ("SJ", "Svalbard and Jan Mayen"), ("SK", "Slovakia"), ("SL", "Sierra Leone"),
+            ("SM", "San Marino"), ("SN", "Senegal"), ("SO", "Somalia"), ("SR", "Suriname"), ("SS", "South Sudan"),
+            ("ST", "Sao Tome and Principe"), ("SV", "El Salvador"), ("SX", "Sint Maarten (Dutch part)"),
+            ("SY", "Syria"), ("SZ", "Eswatini"), ("TC", "Turks and Caicos Islands"), ("TD", "Chad"),
+            ("TF", "French Southern Territories"), ("TG", "Togo"), ("TH", "Thailand"), ("TJ", "Tajikistan"),
+            ("TK", "Tokelau"), ("TL", "Timor-Leste"), ("TM", "Turkmenistan"), ("TN", "Tunisia"), ("TO", "Tonga"),
+            ("TR", "Türkiye"), ("TT", "Trinidad and Tobago"), ("TV", "Tuvalu"), ("TW", "Taiwan"), ("TZ", "Tanzania"),
+            ("UA", "Ukraine"), ("UG", "Uganda"), ("UM", "United States Minor Outlying Islands"),
+            ("US", "United States"), ("UY", "Uruguay"), ("UZ", "Uzbekistan"), ("VA", "Holy See (Vatican City State)"),
+            ("VC", "Saint Vincent and the Grenadines"), ("VE", "Venezuela"), ("VG", "Virgin Islands, British"),
+            ("VI", "Virgin Islands, U.S."), ("VN", "Vietnam"), ("VU", "Vanuatu"), ("WF", "Wallis and Futuna"),
+            ("WS", "Samoa"), ("YE", "Yemen"), ("YT", "Mayotte"), ("ZA", "South Africa"), ("ZM", "Zambia"),
+            ("ZW", "Zimbabwe"),
+        ];
+
+        /// Legal-form suffixes removed by `strip_legal_suffix`; multi-word forms are listed first so they win.
+        const LEGAL_SUFFIXES: &[&str] = &[
+            "Pty Ltd", "S.p.A.", "S.r.l.", "GmbH", "Corp.", "Ltd.", "Inc.", "B.V.", "S.A.", "Corp", "Ltd",
+            "Inc", "LLC", "plc", "Co.", "AG", "BV", "SA",
+        ];
+
+        /// Remove a trailing legal-form suffix such as "GmbH" or "Inc." from an organization name.
+        pub fn strip_legal_suffix(name: &str) -> String {
+            let trimmed = name.trim_end();
+            for suffix in LEGAL_SUFFIXES {
+                let Some(split) = trimmed.len().checked_sub(suffix.len()) else {
+                    continue;
+                };
+                if !trimmed.is_char_boundary(split) || !trimmed[split..].eq_ignore_ascii_case(suffix) {
+                    continue;
+                }
+                let rest = &trimmed[..split];
+                if rest.ends_with([' ', ',']) {
+                    let stripped = rest.trim_end_matches([' ', ',']);
+                    if !stripped.is_empty() {
+                        return stripped.to_string();
+                    }
+                }
+            }
+            trimmed.to_string()
+        }
+
+        /// Drop acronyms that repeat an earlier one case-insensitively, keeping the first casing.
+        pub fn dedupe_acronyms(acronyms: impl IntoIterator<Item = String>) -> Vec<String> {
+            let mut seen = std::collections::HashSet::new();
+            acronyms
+                .into_iter()
+                .filter(|acronym| seen.insert(acronym.to_lowercase()))
+                .collect()
+        }
+
+        /// Write the YAML entries to `yaml_path`, prefixed with a UTF-8 BOM.
+        pub fn write_yaml(yaml_path: &Path, yaml_data: &[YamlEntry]) -> Result<(), Box<dyn Error>> {
+            write_yaml_with_options(yaml_path, yaml_data, &ConvertOptions::default())
+        }
+
+        /// Write the YAML entries to `yaml_path` as configured by `options`.
+        pub fn write_yaml_with_options<T>(
+            yaml_path: &Path,
+            yaml_data: &[T],
+            options: &ConvertOptions,
+        ) -> Result<(), Box<dyn Error>>
+        where
+            T: Serialize + serde::de::DeserializeOwned,
+        {
+            write_entries(open_output(yaml_path, options)?, yaml_data, options)?;
+            apply_output_modes(yaml_path, options)?;
+
+            if options.verify_output {
+                verify_output_as::<T>(yaml_path, yaml_data.len(), options)?;
+            }
+
+            Ok(())
+        }
+
+        /// Create or truncate the output file at `path`.
+        ///
+        /// A missing parent directory is created with `options.create_dirs`, and reported by
+        /// name otherwise rather than as a bare "No such file or directory".
+        pub fn create_output(path: &Path, options: &ConvertOptions) -> std::io::Result<File> {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty() && !parent.is_dir()) {
+                if !options.create_dirs {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("Output directory {} does not exist; pass --create-dirs to create it", parent.display()),
+                    ));
+                }
+                std::fs::create_dir_all(parent)?;
+            }
+            File::create(path)
+        }
+
+        /// Open the output at `yaml_path`, teed into the `options.tee` copy when one is configured.
+        ///
+        /// The copy is only created once the primary output opened, so a failure leaves no
+        /// stray copy behind.
+        pub fn open_output(yaml_path: &Path, options: &ConvertOptions) -> std::io::Result<Tee<BufWriter<File>>> {
+            let primary = BufWriter::new(create_output(yaml_path, options)?);
+            let secondary = match &options.tee {
+                Some(tee_path) => Some(BufWriter::new(create_output(tee_path, options)?)),
+                None => None,
+            };
+            Ok(Tee { primary, secondary })
+        }
+
+        /// A writer that duplicates everything written to `primary` into `secondary`, if any.
+        pub struct Tee<W> {
+            pub primary: W,
+            pub secondary: Option<W>,
+        }
+
+        impl<W: Write> Write for Tee<W> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                // Both copies must get every byte, so partial writes are completed here.
+                self.primary.write_all(buf)?;
+                if let Some(secondary) = &mut self.secondary {
+                    secondary.write_all(buf)?;
+                }
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.primary.flush()?;
+                match &mut self.secondary {
+                    Some(secondary) => secondary.flush(),
+                    None => Ok(()),
+                }
+            }
+        }
+
+        /// Apply the output mode to the written file and its tee copy.
+        fn apply_output_modes(yaml_path: &Path, options: &ConvertOptions) -> std::io::Result<()> {
+            apply_output_mode(yaml_path, options)?;
+            match &options.tee {
+                Some(tee_path) => apply_output_mode(tee_path, options),
+                None => Ok(()),
+            }
+        }
+
+        /// Set the permissions of the written file to `options.output_mode`, if configured.
+        ///
+        /// Only Unix file modes are supported; elsewhere a warning is logged and the file is left as created.
+        pub fn apply_output_mode(path: &Path, options: &ConvertOptions) -> std::io::Result<()> {
+            let Some(mode) = options.output_mode else {
+                return Ok(());
+            };
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            }
+            #[cfg(not(unix))]
+            {
+                options.log(
+                    Verbosity::Normal,
+                    &format!("Warning: ignoring output mode {:o} for {}; file modes are Unix-only", mode, path.display()),
+                );
+                Ok(())
+            }
+        }
+
+        /// Write the YAML document for `yaml_data` to `writer`, including the BOM and version header.
+        pub fn write_entries<W: Write, T: Serialize>(
+            writer: W,
+            yaml_data: &[T],
+            options: &ConvertOptions,
+        ) -> Result<(), Box<dyn Error>> {
+            match options.output_format {
+                OutputFormat::Yaml => write_with(YamlWriter::new(writer, options), yaml_data),
+                OutputFormat::JsonLines => write_with(JsonLinesWriter::new(writer, options), yaml_data),
+            }
+        }
+
+        /// Feed every entry of `yaml_data` to `vocab_writer`, then finish its output.
+        pub fn write_with<T, V: VocabWriter<T>>(mut vocab_writer: V, yaml_data: &[T]) -> Result<(), Box<dyn Error>> {
+            for entry in yaml_data {
+                vocab_writer.write(entry)?;
+            }
+            vocab_writer.finish()
+        }
+
+        /// An output format, serializing converted entries one at a time.
+        ///
+        /// The transformation only produces entries; `write_with` hands them to a writer, so new
+        /// formats plug in by implementing this trait.
+        pub trait VocabWriter<T = YamlEntry> {
+            /// Serialize the next entry.
+            fn write(&mut self, entry: &T) -> Result<(), Box<dyn Error>>;
+
+            /// Complete the output after the last entry and flush it.
+            fn finish(self) -> Result<(), Box<dyn Error>>;
+        }
+
+        /// Writes entries as a YAML list, block-style or, with `compact_yaml`, one flow mapping
+        /// per line, preceded by the BOM and version header.
+        ///
+        /// Entries are written as they arrive, except with `use_anchors`: anchors are chosen
+        /// over the whole document, so its text is kept until `finish`.
+        pub struct YamlWriter<W: Write> {
+            writer: W,
+            // The options to serialize single entries with; anchoring is applied by the writer.
+            options: ConvertOptions,
+            anchored: Option<String>,
+            entries: usize,
+        }
+
+        impl<W: Write> YamlWriter<W> {
+            pub fn new(writer: W, options: &ConvertOptions) -> Self {
+                YamlWriter {
+                    writer,
+                    options: ConvertOptions { use_anchors: false, ..options.clone() },
+                    anchored: options.use_anchors.then(String::new),
+                    entries: 0,
+                }
+            }
+
+            /// Write `yaml`, preceded by the preamble if it starts the output.
+            fn write_text(&mut self, yaml: &str, starts_output: bool) -> std::io::Result<()> {
+                if starts_output {
+                    write_preamble(&mut self.writer, &self.options)?;
+                }
+                write_yaml_text(&mut self.writer, yaml, &self.options)
+            }
+        }
+
+        impl<W: Write, T: Serialize> VocabWriter<T> for YamlWriter<W> {
+            fn write(&mut self, entry: &T) -> Result<(), Box<dyn Error>> {
+                let yaml = entries_to_yaml(std::slice::from_ref(entry), &self.options)?;
+                // Every entry after the first continues the same list, without a document marker.
+                let yaml = if self.entries > 0 { yaml.strip_prefix("---\n").unwrap_or(&yaml) } else { &yaml };
+                match &mut self.anchored {
+                    Some(text) => text.push_str(yaml),
+                    None => self.write_text(yaml, self.entries == 0)?,
+                }
+                self.entries += 1;
+                Ok(())
+            }
+
+            fn finish(mut self) -> Result<(), Box<dyn Error>> {
+                match self.anchored.take() {
+                    Some(text) if self.entries > 0 => self.write_text(&anchor_schemes(&text), true)?,
+                    _ if self.entries == 0 => self.write_text(&entries_to_yaml::<T>(&[], &self.options)?, true)?,
+                    _ => {}
+                }
+                self.writer.flush()?;
+                Ok(())
+            }
+        }
+
+        /// Write the UTF-8 BOM and, when configured, the vocabulary version header.
+        fn write_preamble<W: Write>(writer: &mut W, options: &ConvertOptions) -> std::io::Result<()> {
+            // Optionally, write the UTF-8 BOM to ensure proper encoding detection.
+            writer.write_all(b"\xEF\xBB\xBF")?;
+
+            // Record which registry snapshot produced the file.
+            if let Some(version) = &options.vocab_version {
+                write!(writer, "# vocabulary version: {}{}", version, options.output_newline.as_str())?;
+            }
+            Ok(())
+        }
+
+        /// Write serialized YAML with the configured line endings. Line breaks inside values are
+        /// always escaped by the serializer, so every newline in the text is a line ending.
+        fn write_yaml_text<W: Write>(writer: &mut W, yaml: &str, options: &ConvertOptions) -> std::io::Result<()> {
+            match options.output_newline {
+                Newline::Lf => writer.write_all(yaml.as_bytes()),
+                Newline::Crlf => writer.write_all(yaml.replace('\n', "\r\n").as_bytes()),
+            }
+        }
+
+        /// Writes each entry as a compact JSON object on its own line, with no BOM or header.
+        pub struct JsonLinesWriter<'a, W: Write> {
+            writer: W,
+            options: &'a ConvertOptions,
+        }
+
+        impl<'a, W: Write> JsonLinesWriter<'a, W> {
+            pub fn new(writer: W, options: &'a ConvertOptions) -> Self {
+                JsonLinesWriter { writer, options }
+            }
+        }
+
+        impl<W: Write, T: Serialize> VocabWriter<T> for JsonLinesWriter<'_, W> {
+            fn write(&mut self, entry: &T) -> Result<(), Box<dyn Error>> {
+                if self.options.reshapes_entries() {
+                    let projected = reshape_entries(std::slice::from_ref(entry), self.options)?;
+                    serde_json::to_writer(&mut self.writer, &projected[0])?;
+                } else {
+                    serde_json::to_writer(&mut self.writer, entry)?;
+                }
+                self.writer.write_all(self.options.output_newline.as_str().as_bytes())?;
+                Ok(())
+            }
+
+            fn finish(mut self) -> Result<(), Box<dyn Error>> {
+                self.writer.flush()?;
+                Ok(())
+            }
+        }
+
+        /// Records written between two checkpoint updates.
+        pub const CHECKPOINT_INTERVAL: usize = 1000;
+
+        /// Read the number of input records already written, as recorded in `checkpoint_path`.
+        /// A missing checkpoint means nothing was written yet.
+        pub fn read_checkpoint(checkpoint_path: &Path) -> Result<usize, Box<dyn Error>> {
+            match std::fs::read_to_string(checkpoint_path) {
+                Ok(text) => text.trim().parse().map_err(|_| {
+                    ParseError(format!("Invalid checkpoint {}: expected a record count, got '{}'", checkpoint_path.display(), text.trim()))
+                        .into()
+                }),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        /// Record `records` in the checkpoint, replacing it atomically.
+        fn write_checkpoint(checkpoint_path: &Path, records: usize) -> std::io::Result<()> {
+            let mut staging = checkpoint_path.as_os_str().to_owned();
+            staging.push(".tmp");
+            std::fs::write(&staging, format!("{}\n", records))?;
+            std::fs::rename(&staging, checkpoint_path)
+        }
+
+        /// Write `yaml_data` to `yaml_path`, resuming an interrupted write recorded in `checkpoint_path`.
+        ///
+        /// `records` holds the input record index of each entry, as returned by
+        /// `convert_items_with_records`, out of `record_count` input records. Entries of records
+        /// before the checkpoint are taken to be in the output already; the rest are appended in
+        /// batches of `CHECKPOINT_INTERVAL` records, updating the checkpoint after each batch.
+        /// Without a checkpoint (or output) the file is written from the start. Returns the
+        /// record index the write resumed from.
+        pub fn write_yaml_with_checkpoint(
+            yaml_path: &Path,
+            yaml_data: &[YamlEntry],
+            records: &[usize],
+            record_count: usize,
+            checkpoint_path: &Path,
+            options: &ConvertOptions,
+        ) -> Result<usize, Box<dyn Error>> {
+            if options.compact_yaml || options.use_anchors || options.tee.is_some() || options.output_format != OutputFormat::Yaml {
+                return Err(UnsupportedError(
+                    "--checkpoint cannot be combined with --compact-yaml, --use-anchors, --tee or --format jsonl".to_string(),
+                )
+                .into());
+            }
+
+            let resume_from = if yaml_path.exists() { read_checkpoint(checkpoint_path)? } else { 0 };
+            let mut writer = if resume_from > 0 {
+                BufWriter::new(std::fs::OpenOptions::new().append(true).open(yaml_path)?)
+            } else {
+                let mut writer = BufWriter::new(create_output(yaml_path, options)?);
+                write_preamble(&mut writer, options)?;
+                if yaml_data.is_empty() {
+                    write_yaml_text(&mut writer, &entries_to_yaml(yaml_data, options)?, options)?;
+                }
+                writer
+            };
+
+            // `written` counts the entries already in the output.
+            let mut written = records.partition_point(|&record| record < resume_from);
+            let mut record = resume_from;
+            while record < record_count {
+                let batch_end = (record + CHECKPOINT_INTERVAL).min(record_count);
+                let end = written + records[written..].partition_point(|&record| record < batch_end);
+                if end > written {
+                    let yaml = entries_to_yaml(&yaml_data[written..end], options)?;
+                    // Later batches continue the same sequence, without a document marker.
+                    let yaml = if written > 0 { yaml.strip_prefix("---\n").unwrap_or(&yaml) } else { &yaml };
+                    write_yaml_text(&mut writer, yaml, options)?;
+                    writer.flush()?;
+                    writer.get_ref().sync_data()?;
+                }
+                write_checkpoint(checkpoint_path, batch_end)?;
+                written = end;
+                record = batch_end;
+            }
+            writer.flush()?;
+            drop(writer);
+            apply_output_mode(yaml_path, options)?;
+
+            Ok(resume_from)
+        }
+
+        /// Re-read the written file and check it parses back into `expected` entries.
+        pub fn verify_output(yaml_path: &Path, expected: usize, options: &ConvertOptions) -> Result<(), Box<dyn Error>> {
+            verify_output_as::<YamlEntry>(yaml_path, expected, options)
+        }
+
+        /// Re-read the written file and check it parses back into `expected` entries of type `T`.
+        pub fn verify_output_as<T: serde::de::DeserializeOwned>(
+            yaml_path: &Path,
+            expected: usize,
+            options: &ConvertOptions,
+        ) -> Result<(), Box<dyn Error>> {
+            let content = std::fs::read_to_string(yaml_path)?;
+            let content = content.strip_prefix('\u{FEFF}').unwrap_or(&content);
+            // Reshaped entries lack fields `T` requires, so only count them.
+            let parsed = match options.output_format {
+                OutputFormat::Yaml if !options.reshapes_entries() => serde_yaml::from_str::<Vec<T>>(content)?.len(),
+                OutputFormat::Yaml => serde_yaml::from_str::<Vec<serde_yaml::Value>>(content)?.len(),
+                OutputFormat::JsonLines => {
+                    let mut count = 0;
+                    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                        if options.reshapes_entries() {
+                            serde_json::from_str::<serde_json::Value>(line)?;
+                        } else {
+                            serde_json::from_str::<T>(line)?;
+                        }
+                        count += 1;
+                    }
+                    count
+                }
+            };
+
+            if parsed != expected {
+                return Err(ValidationError(format!(
+                    "Output verification failed for {}: wrote {} entries but read back {}",
+                    yaml_path.display(),
+                    expected,
+                    parsed
+                ))
+                .into());
+            }
+
+            Ok(())
+        }
+
+        /// Serialize the entries to a YAML string, applying the field projection.
+        pub fn entries_to_yaml<T: Serialize>(yaml_data: &[T], options: &ConvertOptions) -> Result<String, Box<dyn Error>> {
+            if !options.reshapes_entries() {
+                if options.compact_yaml {
+                    return compact_yaml(yaml_data);
+                }
+                return Ok(block_yaml(yaml_data, options)?);
+            }
+            let projected = reshape_entries(yaml_data, options)?;
+            if options.compact_yaml {
+                return compact_yaml(&projected);
+            }
+            Ok(block_yaml(&projected, options)?)
+        }
+
+        /// Apply the field projection and identifier key rename to the entries.
+        fn reshape_entries<T: Serialize>(yaml_data: &[T], options: &ConvertOptions) -> Result<Vec<serde_yaml::Value>, Box<dyn Error>> {
+            let mut projected = project_entries(yaml_data, &options.fields)?;
+            if options.identifier_key != DEFAULT_IDENTIFIER_KEY {
+                rename_key(&mut projected, DEFAULT_IDENTIFIER_KEY, &options.identifier_key);
+            }
+            Ok(projected)
+        }
+
+        /// Serialize as block-style YAML, anchoring repeated schemes when `options.use_anchors` is set.
+        fn block_yaml<T: Serialize + ?Sized>(data: &T, options: &ConvertOptions) -> Result<String, serde_yaml::Error> {
+            let yaml = serde_yaml::to_string(data)?;
+            Ok(if options.use_anchors { anchor_schemes(&yaml) } else { yaml })
+        }
+
+        /// Replace repeated `scheme:` values in block-style YAML with an anchor and aliases.
+        ///
+        /// The first occurrence of a value that appears more than once becomes
+        /// `scheme: &scheme_ror ror` and later ones `scheme: *scheme_ror`, which parse back
+        /// to the same strings.
+        pub fn anchor_schemes(yaml: &str) -> String {
+            const KEY: &str = "scheme: ";
+
+            /// The scheme value of a line holding only a `scheme` mapping entry, if any.
+            fn scheme_value(line: &str) -> Option<&str> {
+                let content = line.trim_start();
+                let content = content.strip_prefix("- ").unwrap_or(content);
+                content.strip_prefix(KEY).filter(|value| !value.is_empty() && !value.starts_with(['&', '*']))
+            }
+
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for value in yaml.lines().filter_map(scheme_value) {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+
+            let mut anchors: HashMap<&str, (String, bool)> = HashMap::new();
+            let mut used_names = std::collections::HashSet::new();
+            let mut out = String::with_capacity(yaml.len());
+            for line in yaml.split_inclusive('\n') {
+                let Some(value) = scheme_value(line.trim_end_matches('\n')).filter(|value| counts[value] > 1) else {
+                    out.push_str(line);
+                    continue;
+                };
+                let (name, defined) = anchors.entry(value).or_insert_with(|| {
+                    let base: String = value
+                        .chars()
+                        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+                        .collect();
+                    let mut name = format!("scheme_{}", base);
+                    let mut suffix = 2;
+                    while !used_names.insert(name.clone()) {
+                        name = format!("scheme_{}_{}", base, suffix);
+                        suffix += 1;
+                    }
+                    (name, false)
+                });
+                let prefix_len = line.find(KEY).map(|start| start + KEY.len()).unwrap_or_default();
+                out.push_str(&line[..prefix_len]);
+                if *defined {
+                    out.push('*');
+                    out.push_str(name);
+                } else {
+                    out.push('&');
+                    out.push_str(name);
+                    out.push(' ');
+                    out.push_str(value);
+                    *defined = true;
+                }
+                if line.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            out
+        }
+
+        /// Serialize each item as a single-line flow mapping within a block sequence.
+        ///
+        /// JSON is a subset of YAML flow style, so each entry is written as compact JSON.
+        fn compact_yaml<T: Serialize>(items: &[T]) -> Result<String, Box<dyn Error>> {
+            if items.is_empty() {
+                return Ok("---\n[]\n".to_string());
+            }
+            let mut out = String::from("---\n");
+            for item in items {
+                out.push_str("- ");
+                out.push_str(&serde_json::to_string(item)?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+
+        /// Convert entries into dynamic YAML mappings holding only the projected fields.
+        pub fn project_entries<T: Serialize>(
+            yaml_data: &[T],
+            projection: &FieldProjection,
+        ) -> Result<Vec<serde_yaml::Value>, Box<dyn Error>> {
+            let mut projected = Vec::with_capacity(yaml_data.len());
+            for entry in yaml_data {
+                let mut value = serde_yaml::to_value(entry)?;
+                if let serde_yaml::Value::Mapping(mapping) = &mut value {
+                    let dropped: Vec<serde_yaml::Value> = mapping
+                        .iter()
+                        .map(|(key, _)| key.clone())
+                        .filter(|key| !key.as_str().is_some_and(|k| projection.keeps(k)))
+                        .collect();
+                    for key in dropped {
+                        mapping.remove(&key);
+                    }
+                }
+                projected.push(value);
+            }
+            Ok(projected)
+        }
+
+        /// Rename the top-level key `from` to `to` in every mapping, keeping its position.
+        fn rename_key(entries: &mut [serde_yaml::Value], from: &str, to: &str) {
+            for entry in entries {
+                if let serde_yaml::Value::Mapping(mapping) = entry {
+                    *mapping = std::mem::take(mapping)
+                        .into_iter()
+                        .map(|(key, value)| match key.as_str() {
+                            Some(k) if k == from => (serde_yaml::Value::String(to.to_string()), value),
+                            _ => (key, value),
+                        })
+                        .collect();
+                }
+            }
+        }
+
+        /// Read a previously written YAML vocabulary file, skipping the BOM if present.
+        pub fn read_yaml(yaml_path: &Path) -> Result<Vec<YamlEntry>, Box<dyn Error>> {
+            let content = std::fs::read_to_string(yaml_path)?;
+            let content = content.strip_prefix('\u{FEFF}').unwrap_or(&content);
+            Ok(serde_yaml::from_str(content)?)
+        }
+
+        /// Keep only the entries that are new or differ from the baseline entry with the same id.
+        pub fn changed_entries(yaml_data: Vec<YamlEntry>, baseline: &[YamlEntry]) -> Vec<YamlEntry> {
+            let previous: HashMap<&str, &YamlEntry> =
+                baseline.iter().map(|entry| (entry.id.as_str(), entry)).collect();
+            yaml_data
+                .into_iter()
+                .filter(|entry| previous.get(entry.id.as_str()) != Some(&entry))
+                .collect()
+        }
+
+        /// Read a written vocabulary file of any type as generic YAML entries, skipping the BOM.
+        pub fn read_yaml_values(yaml_path: &Path) -> Result<Vec<serde_yaml::Value>, Box<dyn Error>> {
+            let content = std::fs::read_to_string(yaml_path)?;
+            let content = content.strip_prefix('\u{FEFF}').unwrap_or(&content);
+            serde_yaml::from_str(content)
+                .map_err(|err| ParseError(format!("Invalid vocabulary file {}: {}", yaml_path.display(), err)).into())
+        }
+
+        /// Render a markdown changelog of the entries added, removed and renamed between two
+        /// snapshots of a vocabulary.
+        ///
+        /// Entries are matched by `id`; an entry is renamed when its `name` (or `subject`)
+        /// changed. Added and renamed entries are listed in `new` order, removed ones in `old` order.
+        pub fn changelog(old: &[serde_yaml::Value], new: &[serde_yaml::Value]) -> String {
+            fn id_and_name(entry: &serde_yaml::Value) -> Option<(&str, &str)> {
+                let id = entry.get("id").and_then(serde_yaml::Value::as_str).filter(|id| !id.is_empty())?;
+                let name = ["name", "subject"]
+                    .iter()
+                    .find_map(|field| entry.get(*field).and_then(serde_yaml::Value::as_str))
+                    .unwrap_or_default();
+                Some((id, name))
+            }
+
+            let old_names: HashMap<&str, &str> = old.iter().filter_map(id_and_name).collect();
+            let new_names: HashMap<&str, &str> = new.iter().filter_map(id_and_name).collect();
+
+            let mut added = Vec::new();
+            let mut renamed = Vec::new();
+            for (id, name) in new.iter().filter_map(id_and_name) {
+                match old_names.get(id) {
+                    None => added.push(format!("- `{}` {}", id, name)),
+                    Some(&previous) if previous != name => renamed.push(format!("- `{}` {} -> {}", id, previous, name)),
+                    Some(_) => {}
+                }
+            }
+            let removed: Vec<String> = old
+                .iter()
+                .filter_map(id_and_name)
+                .filter(|(id, _)| !new_names.contains_key(id))
+                .map(|(id, name)| format!("- `{}` {}", id, name))
+                .collect();
+
+            let mut markdown = String::from("# Changelog\n");
+            for (title, lines) in [("Added", added), ("Removed", removed), ("Renamed", renamed)] {
+                markdown.push_str(&format!("\n## {} ({})\n\n", title, lines.len()));
+                if lines.is_empty() {
+                    markdown.push_str("None.\n");
+                }
+                for line in lines {
+                    markdown.push_str(&line);
+                    markdown.push('\n');
+                }
+            }
+            markdown
+        }
+
+        /// Build a flat `{ id: name }` map from serialized entries.
+        ///
+        /// The name is the entry's `name` field, or `subject` for subject entries; entries
+        /// without an id or a name are left out.
+        pub fn lookup_map<T: Serialize>(yaml_data: &[T]) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn Error>> {
+            let mut lookup = serde_json::Map::new();
+            for entry in yaml_data {
+                let value = serde_json::to_value(entry)?;
+                let id = value.get("id").and_then(serde_json::Value::as_str).unwrap_or_default();
+                let name = ["name", "subject"]
+                    .iter()
+                    .find_map(|field| value.get(*field).and_then(serde_json::Value::as_str))
+                    .unwrap_or_default();
+                if !id.is_empty() && !name.is_empty() {
+                    lookup.insert(id.to_string(), serde_json::Value::String(name.to_string()));
+                }
+            }
+            Ok(lookup)
+        }
+
+        /// Write the compact `{ id: name }` lookup JSON for `yaml_data` to `lookup_path`.
+        pub fn write_lookup<T: Serialize>(lookup_path: &Path, yaml_data: &[T]) -> Result<(), Box<dyn Error>> {
+            let mut writer = BufWriter::new(File::create(lookup_path)?);
+            serde_json::to_writer(&mut writer, &lookup_map(yaml_data)?)?;
+            writeln!(writer)?;
+            writer.flush()?;
+            Ok(())
+        }
+
+        /// Read a golden example of a correctly shaped entry (or a list whose first item is one).
+        pub fn read_golden(golden_path: &Path) -> Result<serde_yaml::Value, Box<dyn Error>> {
+            let golden: serde_yaml::Value = serde_yaml::from_reader(BufReader::new(File::open(golden_path)?))?;
+            let golden = match golden {
+                serde_yaml::Value::Sequence(mut entries) if !entries.is_empty() => entries.swap_remove(0),
+                golden => golden,
+            };
+            if !golden.is_mapping() {
+                return Err(ParseError(format!("Golden file {} does not hold a mapping", golden_path.display())).into());
+            }
+            Ok(golden)
+        }
+
+        /// Check that every entry, as it would be written with `options`, has all the keys of `golden`.
+        ///
+        /// Nested mappings are compared recursively and list items against the golden's first
+        /// item. Keys beyond the golden's are allowed, so optional fields need not appear in it.
+        pub fn check_golden<T: Serialize>(golden: &serde_yaml::Value, yaml_data: &[T], options: &ConvertOptions) -> Result<(), Box<dyn Error>> {
+            let emitted: Vec<serde_yaml::Value> = serde_yaml::from_str(&entries_to_yaml(yaml_data, options)?)?;
+            for (index, entry) in emitted.iter().enumerate() {
+                let mut missing = Vec::new();
+                missing_keys(golden, entry, "", &mut missing);
+                if !missing.is_empty() {
+                    let id = entry.get("id").and_then(serde_yaml::Value::as_str).unwrap_or_default();
+                    return Err(ValidationError(format!(
+                        "Entry {} (id '{}') does not match the golden shape: missing {}",
+                        index,
+                        id,
+                        missing.join(", ")
+                    ))
+                    .into());
+                }
+            }
+            Ok(())
+        }
+
+        /// Collect the paths of keys present in `golden` but absent from `value`.
+        fn missing_keys(golden: &serde_yaml::Value, value: &serde_yaml::Value, path: &str, missing: &mut Vec<String>) {
+            match (golden, value) {
+                (serde_yaml::Value::Mapping(golden), serde_yaml::Value::Mapping(value)) => {
+                    for (key, golden_child) in golden {
+                        let name = key.as_str().map(str::to_string).unwrap_or_else(|| format!("{:?}", key));
+                        let child_path = if path.is_empty() { name } else { format!("{}.{}", path, name) };
+                        match value.get(key) {
+                            Some(child) => missing_keys(golden_child, child, &child_path, missing),
+                            None => missing.push(child_path),
+                        }
+                    }
+                }
+                (serde_yaml::Value::Sequence(golden), serde_yaml::Value::Sequence(values)) => {
+                    if let Some(golden_item) = golden.first() {
+                        let item_path = format!("{}[]", path);
+                        for item in values {
+                            missing_keys(golden_item, item, &item_path, missing);
+                        }
+                    }
+                }
+                // A mapping in the golden needs a mapping here; scalars are not compared.
+                (serde_yaml::Value::Mapping(_), _) => missing.push(path.to_string()),
+                _ => {}
+            }
+        }
+
+        /// Count how many entries carry a title in each language code.
+        pub fn language_stats(yaml_data: &[YamlEntry]) -> BTreeMap<String, usize> {
+            let mut counts = BTreeMap::new();
+            for entry in yaml_data {
+                for lang in entry.title.keys() {
+                    *counts.entry(lang.clone()).or_insert(0) += 1;
+                }
+            }
+            counts
+        }
+
+        /// Write a per-language title count table to `stats_path`.
+        pub fn write_language_stats(stats_path: &Path, yaml_data: &[YamlEntry]) -> Result<(), Box<dyn Error>> {
+            let file = File::create(stats_path)?;
+            let mut writer = BufWriter::new(file);
+
+            writeln!(writer, "language\tentries")?;
+            for (lang, count) in language_stats(yaml_data) {
+                writeln!(writer, "{}\t{}", lang, count)?;
+            }
+            writer.flush()?;
+
+            Ok(())
+        }
+    }
+
+    /// Module for converting a Names vocabulary (e.g. from an ORCID dump).
+    pub mod names {
+        use super::affiliations::{
+            deserialize_null_default, read_json_document, ConvertOptions, Identifier, NameFormat, Nullable, RedactedField,
+        };
+        use super::VocabType;
+        use schemars::JsonSchema;
         use serde::{Deserialize, Serialize};
-        use std::collections::HashMap;
+        use std::collections::{HashMap, HashSet};
         use std::error::Error;
         use std::fs::File;
         use std::io::{BufReader, BufWriter, Write};
         use std::path::Path;
 
-        #[derive(Debug, Deserialize)]
-        pub struct AffiliationItem {
-            #[serde(deserialize_with = "deserialize_null_default")]
+        #[derive(Debug, Deserialize, JsonSchema)]
+        pub struct NameItem {
+            /// ORCID, bare or as a URL.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
             pub id: String,
-            #[serde(deserialize_with = "deserialize_null_default")]
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub given_name: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub family_name: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<AffiliationRef>>")]
+            pub affiliations: Vec<AffiliationRef>,
+        }
+
+        /// An affiliation referenced by a name record.
+        #[derive(Debug, Deserialize, JsonSchema)]
+        pub struct AffiliationRef {
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub id: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
             pub name: String,
-            #[serde(default)]
-            pub labels: Vec<Label>,
-            #[serde(default)]
-            pub acronyms: Vec<String>,
         }
 
-        #[derive(Debug, Deserialize)]
-        pub struct Label {
-            #[serde(deserialize_with = "deserialize_null_default")]
-            pub iso639: String,
-            #[serde(deserialize_with = "deserialize_null_default")]
-            pub label: String,
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct NameEntry {
+            pub id: String,
+            pub name: String,
+            #[serde(default, skip_serializing_if = "String::is_empty")]
+            pub given_name: String,
+            #[serde(default, skip_serializing_if = "String::is_empty")]
+            pub family_name: String,
+            pub identifiers: Vec<Identifier>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub affiliations: Vec<NameAffiliation>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub tags: Vec<String>,
         }
 
-        pub fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
-        where
-            T: Default + Deserialize<'de>,
-            D: serde::Deserializer<'de>,
-        {
-            let opt = Option::deserialize(deserializer)?;
-            Ok(opt.unwrap_or_default())
+        /// An affiliation of a name entry: the affiliation's id when known, and its name.
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct NameAffiliation {
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub id: Option<String>,
+            #[serde(default, skip_serializing_if = "String::is_empty")]
+            pub name: String,
         }
 
-        /// Sanitize a string by transliterating ambiguous Unicode characters (such as Cyrillic)
-        /// into their approximate ASCII equivalents.
-        pub fn sanitize(s: &str) -> String {
-            deunicode(s)
+        impl NameAffiliation {
+            /// Whether `other` refers to the same affiliation: by id when either has one, else by name.
+            pub fn same_as(&self, other: &NameAffiliation) -> bool {
+                match (&self.id, &other.id) {
+                    (None, None) => self.name == other.name,
+                    (id, other_id) => id == other_id,
+                }
+            }
         }
 
-        #[derive(Debug, Serialize, Deserialize, PartialEq)]
-        pub struct YamlEntry {
+        /// Input records that were merged into a single entry because they share an ORCID.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct DuplicateGroup {
+            /// The emitted id the records were merged under.
             pub id: String,
-            pub name: String,
-            pub title: HashMap<String, String>,
-            pub identifiers: Vec<Identifier>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            pub acronym: Option<String>,
+            /// Zero-based index of the record that was kept.
+            pub kept: usize,
+            /// Zero-based indices of the records merged into it, in input order.
+            pub dropped: Vec<usize>,
         }
 
-        #[derive(Debug, Serialize, Deserialize, PartialEq)]
-        pub struct Identifier {
-            pub identifier: String,
-            pub scheme: String,
+        /// Read a JSON file containing Names data and convert it into YAML entries.
+        pub fn convert_json_to_entries(
+            json_path: &Path,
+            options: &ConvertOptions,
+        ) -> Result<Vec<NameEntry>, Box<dyn Error>> {
+            let items = read_items(json_path, options)?;
+            convert_items(&items, options)
         }
 
-        /// Convert a JSON file containing Affiliations data into a YAML file.
-        /// This function sanitizes all strings to replace ambiguous characters.
-        pub fn convert_json_to_yaml(json_path: &Path, yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            // Open and deserialize the JSON file.
+        /// Read a JSON array of `NameItem`s.
+        pub fn read_items(json_path: &Path, options: &ConvertOptions) -> Result<Vec<NameItem>, Box<dyn Error>> {
             let file = File::open(json_path)?;
-            let reader = BufReader::new(file);
-            let items: Vec<AffiliationItem> = serde_json::from_reader(reader)?;
-
-            let mut yaml_data = Vec::new();
+            read_items_from(BufReader::new(file), options)
+        }
 
-            for item in items {
-                // Sanitize the id and extract the last segment.
-                let id_sanitized = sanitize(&item.id);
-                let id_part = id_sanitized.split('/').last().unwrap_or_default().to_string();
+        /// Read a JSON array of `NameItem`s from any reader.
+        pub fn read_items_from<R: std::io::Read>(reader: R, options: &ConvertOptions) -> Result<Vec<NameItem>, Box<dyn Error>> {
+            super::gzip::with_decompressed(reader, |reader| read_json_document(reader, options))
+        }
 
-                let mut title = std::collections::HashMap::new();
-                title.insert("en".to_string(), sanitize(&item.name));
+        /// Count the entries `convert_items` would emit for the records in `reader`, without
+        /// converting them.
+        ///
+        /// Records sharing an ORCID count once, as they are merged. A plain JSON array is streamed
+        /// one record at a time; other inputs are read whole first.
+        pub fn count_items_from<R: std::io::Read>(reader: R, options: &ConvertOptions) -> Result<usize, Box<dyn Error>> {
+            let mut count = 0;
+            let mut seen = HashSet::new();
+            let mut tally = |item: NameItem| -> Result<(), Box<dyn Error>> {
+                let orcid = orcid(&item, options);
+                // Records without an ORCID are never merged.
+                if orcid.is_empty() || seen.insert(orcid) {
+                    count += 1;
+                }
+                Ok(())
+            };
 
-                // Process and sanitize any labels.
-                for label in &item.labels {
-                    if !label.iso639.is_empty() && !label.label.is_empty() {
-                        title.insert(sanitize(&label.iso639), sanitize(&label.label));
-                    }
+            if options.records_path.is_none() && !options.strict_utf8 {
+                super::gzip::with_decompressed(reader, |reader| super::stream_records(reader, options.lenient, &mut tally))?;
+            } else {
+                for item in read_items_from(reader, options)? {
+                    tally(item)?;
                 }
+            }
+            Ok(count)
+        }
 
-                // Get the first non-empty acronym, if available.
-                let acronym = item.acronyms.iter()
-                    .find(|s| !s.is_empty())
-                    .map(|s| sanitize(s));
+        /// Convert `NameItem`s into YAML entries, merging records that share an ORCID.
+        pub fn convert_items(items: &[NameItem], options: &ConvertOptions) -> Result<Vec<NameEntry>, Box<dyn Error>> {
+            convert_items_with_duplicates(items, options).map(|(entries, _)| entries)
+        }
 
-                let identifier = Identifier {
-                    identifier: id_part.clone(),
-                    scheme: "affiliation".to_string(),
-                };
+        /// Like `convert_items`, but also return which input records were merged away.
+        pub fn convert_items_with_duplicates(
+            items: &[NameItem],
+            options: &ConvertOptions,
+        ) -> Result<(Vec<NameEntry>, Vec<DuplicateGroup>), Box<dyn Error>> {
+            let mut yaml_data: Vec<NameEntry> = Vec::with_capacity(items.len());
+            let mut duplicates: Vec<DuplicateGroup> = Vec::new();
+            // Maps an ORCID to its entry index and, once a duplicate shows up, its group index.
+            let mut by_orcid: HashMap<String, (usize, Option<usize>)> = HashMap::new();
+            let mut first_index: Vec<usize> = Vec::with_capacity(items.len());
 
-                let yaml_entry = YamlEntry {
-                    id: id_part,
-                    name: sanitize(&item.name),
-                    title,
-                    identifiers: vec![identifier],
-                    acronym,
-                };
+            for (record, item) in items.iter().enumerate() {
+                let entry = convert_item(item, options);
 
-                yaml_data.push(yaml_entry);
+                // Records without an ORCID cannot be matched and are kept as-is.
+                if entry.id.is_empty() {
+                    first_index.push(record);
+                    yaml_data.push(entry);
+                    continue;
+                }
+
+                match by_orcid.get_mut(&entry.id) {
+                    Some((index, group)) => {
+                        let group = *group.get_or_insert_with(|| {
+                            duplicates.push(DuplicateGroup { id: entry.id.clone(), kept: first_index[*index], dropped: Vec::new() });
+                            duplicates.len() - 1
+                        });
+                        duplicates[group].dropped.push(record);
+                        merge_affiliations(&mut yaml_data[*index], entry.affiliations);
+                    }
+                    None => {
+                        by_orcid.insert(entry.id.clone(), (yaml_data.len(), None));
+                        first_index.push(record);
+                        yaml_data.push(entry);
+                    }
+                }
             }
 
-            // Create file and wrap with BufWriter.
-            let file = File::create(yaml_path)?;
-            let mut writer = BufWriter::new(file);
+            Ok((yaml_data, duplicates))
+        }
 
-            // Optionally, write the UTF-8 BOM to ensure proper encoding detection.
-            writer.write_all(b"\xEF\xBB\xBF")?;
+        /// Write a tab-separated report of the merged records to `report_path`.
+        ///
+        /// Each line lists the merged id, the kept record's index and the dropped indices.
+        pub fn write_duplicates_report(report_path: &Path, duplicates: &[DuplicateGroup]) -> Result<(), Box<dyn Error>> {
+            let mut writer = BufWriter::new(File::create(report_path)?);
 
-            // Serialize the data to YAML.
-            serde_yaml::to_writer(&mut writer, &yaml_data)?;
+            writeln!(writer, "id\tkept\tdropped")?;
+            for group in duplicates {
+                let dropped: Vec<String> = group.dropped.iter().map(usize::to_string).collect();
+                writeln!(writer, "{}\t{}\t{}", group.id, group.kept, dropped.join(","))?;
+            }
+            writer.flush()?;
 
             Ok(())
         }
-    }
 
-    // Placeholder modules for future controlled vocabularies.
+        /// Add the affiliations `entry` does not already list.
+        fn merge_affiliations(entry: &mut NameEntry, affiliations: Vec<NameAffiliation>) {
+            for affiliation in affiliations {
+                if !entry.affiliations.iter().any(|known| known.same_as(&affiliation)) {
+                    entry.affiliations.push(affiliation);
+                }
+            }
+        }
 
-    pub mod names {
-        use std::error::Error;
-        use std::path::Path;
+        /// The ORCID of `item`: the last path segment of its id (e.g. https://orcid.org/0000-...).
+        fn orcid(item: &NameItem, options: &ConvertOptions) -> String {
+            let id_sanitized = options.clean(&item.id);
+            id_sanitized.split('/').next_back().unwrap_or_default().to_string()
+        }
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Names vocabulary conversion not yet implemented.".into())
+        /// Placeholder written in place of fields listed in `ConvertOptions::redact`.
+        pub const REDACTED: &str = "[REDACTED]";
+
+        /// Convert a single `NameItem` into its YAML entry.
+        pub fn convert_item(item: &NameItem, options: &ConvertOptions) -> NameEntry {
+            let orcid = orcid(item, options);
+
+            // Redaction happens before the display name is composed so it cannot leak the field.
+            let redact = |field: RedactedField, value: String| {
+                if !value.is_empty() && options.redact.contains(&field) { REDACTED.to_string() } else { value }
+            };
+            let given_name = redact(RedactedField::GivenName, options.clean(&item.given_name));
+            let family_name = redact(RedactedField::FamilyName, options.clean(&item.family_name));
+            let name = match (family_name.is_empty(), given_name.is_empty()) {
+                (false, false) => match options.name_format {
+                    NameFormat::FamilyCommaGiven => format!("{}, {}", family_name, given_name),
+                    NameFormat::GivenFamily => format!("{} {}", given_name, family_name),
+                },
+                (false, true) => family_name.clone(),
+                _ => given_name.clone(),
+            };
+
+            // Affiliations are referenced by their bare id (e.g. a ROR id) when the record has one.
+            let mut affiliations: Vec<NameAffiliation> = Vec::new();
+            for affiliation in &item.affiliations {
+                let id = options.clean(&affiliation.id);
+                let id = id.split('/').next_back().unwrap_or_default();
+                let affiliation = NameAffiliation {
+                    id: Some(id.to_string()).filter(|id| !id.is_empty()),
+                    name: options.clean(&affiliation.name),
+                };
+                let usable = affiliation.id.is_some() || !affiliation.name.is_empty();
+                if usable && !affiliations.iter().any(|known| known.same_as(&affiliation)) {
+                    affiliations.push(affiliation);
+                }
+            }
+
+            let identifiers = if orcid.is_empty() {
+                Vec::new()
+            } else {
+                vec![Identifier {
+                    identifier: orcid.clone(),
+                    scheme: options.scheme_for(VocabType::Names),
+                }]
+            };
+
+            NameEntry {
+                id: options.entry_id(orcid),
+                name,
+                given_name,
+                family_name,
+                identifiers,
+                affiliations,
+                tags: options.entry_tags(&[]),
+            }
         }
     }
 
+    // Placeholder modules for future controlled vocabularies.
+
     pub mod funding {
         use std::error::Error;
         use std::path::Path;
@@ -143,23 +4407,248 @@ pub mod vocab {
         }
     }
 
+    /// Module for converting an Awards vocabulary (e.g. from an OpenAIRE projects dump).
     pub mod awards {
+        use super::affiliations::{deserialize_null_default, read_json_document, ConvertOptions, Nullable};
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+        use std::collections::BTreeMap;
         use std::error::Error;
+        use std::fs::File;
+        use std::io::BufReader;
         use std::path::Path;
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Awards vocabulary conversion not yet implemented.".into())
+        #[derive(Debug, Deserialize, JsonSchema)]
+        pub struct AwardItem {
+            /// Award id; defaults to `<funder id>::<number>`.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub id: String,
+            /// Grant number assigned by the funder.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub number: String,
+            /// English title.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub title: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub acronym: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub program: String,
+            #[serde(default)]
+            pub funder: Option<FunderRef>,
+            /// Free-text summary of the award, also read from `abstract`.
+            #[serde(default, alias = "abstract", deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub description: String,
+        }
+
+        /// The funder of an award: a `{ id, name }` object or just its id.
+        #[derive(Debug, Deserialize, JsonSchema)]
+        #[serde(untagged)]
+        pub enum FunderRef {
+            Id(String),
+            Object {
+                #[serde(default, deserialize_with = "deserialize_null_default")]
+                #[schemars(with = "Nullable<String>")]
+                id: String,
+            },
+        }
+
+        impl FunderRef {
+            /// The funder id as given, e.g. a ROR URL or bare ROR id.
+            pub fn id(&self) -> &str {
+                match self {
+                    FunderRef::Id(id) | FunderRef::Object { id } => id,
+                }
+            }
+        }
+
+        /// An award in the InvenioRDM awards vocabulary shape.
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct AwardEntry {
+            pub id: String,
+            #[serde(default, skip_serializing_if = "String::is_empty")]
+            pub number: String,
+            pub title: BTreeMap<String, String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub acronym: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub program: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub funder: Option<FunderEntry>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub description: Option<String>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub tags: Vec<String>,
+        }
+
+        /// Reference from an award entry to its funder entry.
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct FunderEntry {
+            pub id: String,
+        }
+
+        /// Read a JSON array of `AwardItem`s and convert them into YAML entries.
+        pub fn convert_json_to_entries(json_path: &Path, options: &ConvertOptions) -> Result<Vec<AwardEntry>, Box<dyn Error>> {
+            let items = read_items(json_path, options)?;
+            Ok(items.iter().map(|item| convert_item(item, options)).collect())
+        }
+
+        /// Read a JSON array of `AwardItem`s.
+        pub fn read_items(json_path: &Path, options: &ConvertOptions) -> Result<Vec<AwardItem>, Box<dyn Error>> {
+            let file = File::open(json_path)?;
+            read_items_from(BufReader::new(file), options)
+        }
+
+        /// Read a JSON array of `AwardItem`s from any reader.
+        pub fn read_items_from<R: std::io::Read>(reader: R, options: &ConvertOptions) -> Result<Vec<AwardItem>, Box<dyn Error>> {
+            super::gzip::with_decompressed(reader, |reader| read_json_document(reader, options))
+        }
+
+        /// Convert a single `AwardItem` into the InvenioRDM award shape.
+        pub fn convert_item(item: &AwardItem, options: &ConvertOptions) -> AwardEntry {
+            // Funders are referenced by their bare id (e.g. a ROR id).
+            let funder = item
+                .funder
+                .as_ref()
+                .map(|funder| options.clean(funder.id()))
+                .map(|id| id.split('/').next_back().unwrap_or_default().to_string())
+                .filter(|id| !id.is_empty());
+            let number = options.clean(&item.number);
+
+            let id = match (options.clean(&item.id), &funder) {
+                (id, Some(funder)) if id.is_empty() && !number.is_empty() => format!("{}::{}", funder, number),
+                (id, _) => id,
+            };
+
+            let mut title = BTreeMap::new();
+            if !item.title.is_empty() {
+                title.insert("en".to_string(), options.clean(&item.title));
+            }
+
+            AwardEntry {
+                id: options.entry_id(id),
+                number,
+                title,
+                acronym: Some(options.clean(&item.acronym)).filter(|acronym| !acronym.is_empty()),
+                program: Some(options.clean(&item.program)).filter(|program| !program.is_empty()),
+                funder: funder.map(|id| FunderEntry { id }),
+                description: options.description(&item.description),
+                tags: options.entry_tags(&[]),
+            }
         }
     }
 
+    /// Module for converting a Subjects vocabulary (e.g. from a MeSH dump).
     pub mod subjects {
+        use super::affiliations::{deserialize_null_default, read_json_document, ConvertOptions, Label, Nullable};
+        use super::VocabType;
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+        use std::collections::BTreeMap;
         use std::error::Error;
+        use std::fs::File;
+        use std::io::BufReader;
         use std::path::Path;
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Subjects vocabulary conversion not yet implemented.".into())
+        #[derive(Debug, Deserialize, JsonSchema)]
+        pub struct SubjectItem {
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub id: String,
+            /// Preferred (English) label.
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<String>")]
+            pub subject: String,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<Label>>")]
+            pub labels: Vec<Label>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<String>>")]
+            pub broader: Vec<String>,
+            #[serde(default, deserialize_with = "deserialize_null_default")]
+            #[schemars(with = "Nullable<Vec<String>>")]
+            pub narrower: Vec<String>,
+        }
+
+        /// A subject in the InvenioRDM subjects vocabulary shape.
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct SubjectEntry {
+            pub id: String,
+            pub scheme: String,
+            pub subject: String,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub tags: Vec<String>,
+        }
+
+        /// A subject in a SKOS-style concept shape.
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        pub struct SkosEntry {
+            pub id: String,
+            #[serde(rename = "inScheme")]
+            pub in_scheme: String,
+            #[serde(rename = "prefLabel")]
+            pub pref_label: BTreeMap<String, String>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub broader: Vec<String>,
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub narrower: Vec<String>,
+        }
+
+        /// Read a JSON array of `SubjectItem`s.
+        pub fn read_items(json_path: &Path, options: &ConvertOptions) -> Result<Vec<SubjectItem>, Box<dyn Error>> {
+            let file = File::open(json_path)?;
+            read_items_from(BufReader::new(file), options)
+        }
+
+        /// Read a JSON array of `SubjectItem`s from any reader.
+        pub fn read_items_from<R: std::io::Read>(reader: R, options: &ConvertOptions) -> Result<Vec<SubjectItem>, Box<dyn Error>> {
+            super::gzip::with_decompressed(reader, |reader| read_json_document(reader, options))
+        }
+
+        /// Convert a single `SubjectItem` into the InvenioRDM subject shape.
+        pub fn convert_item(item: &SubjectItem, options: &ConvertOptions) -> SubjectEntry {
+            SubjectEntry {
+                id: options.entry_id(options.clean(&item.id)),
+                scheme: options.scheme_for(VocabType::Subjects),
+                subject: options.clean(&item.subject),
+                tags: options.entry_tags(&[]),
+            }
+        }
+
+        /// Convert a single `SubjectItem` into a SKOS-style concept.
+        pub fn convert_item_skos(item: &SubjectItem, options: &ConvertOptions) -> SkosEntry {
+            let mut pref_label = BTreeMap::new();
+            if !item.subject.is_empty() {
+                pref_label.insert("en".to_string(), options.clean(&item.subject));
+            }
+            for label in &item.labels {
+                if !label.iso639.is_empty() && !label.label.is_empty() {
+                    let lang = options.clean(&label.iso639);
+                    let base = options.title_language(&lang);
+                    if base == lang || !pref_label.contains_key(base) {
+                        pref_label.insert(base.to_string(), options.clean(&label.label));
+                    }
+                }
+            }
+            let id = options.clean(&item.id);
+            for (lang, value) in options.included_labels_for(id.split('/').next_back().unwrap_or_default()) {
+                pref_label.insert(lang.clone(), options.clean(value));
+            }
+            let primary = pref_label.get("en").cloned();
+            pref_label.retain(|lang, value| options.keeps_title(lang, value, primary.as_deref()));
+
+            SkosEntry {
+                id: options.entry_id(id),
+                in_scheme: options.scheme_for(VocabType::Subjects),
+                pref_label,
+                broader: item.broader.iter().map(|id| options.clean(id)).collect(),
+                narrower: item.narrower.iter().map(|id| options.clean(id)).collect(),
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -1,226 +1,1127 @@
 //! Controlled Vocabulary Converter
 //!
 //! Usage:
-//!   vocab_converter <VOCAB_TYPE> <INPUT_JSON> <OUTPUT_YAML>
+//!   vocab_converter [OPTIONS] <VOCAB_TYPE> <INPUT_JSON> <OUTPUT_YAML>
+//!   vocab_converter --fixtures-dir DIR [OPTIONS] <VOCAB_TYPE> <INPUT_JSON>
+//!   vocab_converter --dump-schema <VOCAB_TYPE>
+//!   vocab_converter [OPTIONS] --manifest-run <MANIFEST_TOML>
+//!   vocab_converter [--max-errors N] [--strict-fields] validate <VOCAB_TYPE> <INPUT_JSON>
+//!   vocab_converter changelog <OLD_YAML> <NEW_YAML>
 //!
 //! VOCAB_TYPE can be one of:
 //!   affiliations  - converts affiliations (e.g. from a ROR dump)
-//!   names         - converts names
+//!   names         - converts names (e.g. from an ORCID dump), merging records by ORCID
 //!   funding       - converts funding records
 //!   awards        - converts awards information
-//!   subjects      - converts subject data
+//!   subjects      - converts subject data (InvenioRDM or, with --skos, SKOS-style concepts)
+//!
+//! OPTIONS:
+//!   -q, --quiet        only print errors
+//!   -v, --verbose      also print per-record diagnostics such as skipped records
+//!   -vv                also print detailed transformation output
+//!   --trace-record ID  log every transformation step of the record with id ID (implies -vv)
+//!   --input-format F   format of the input file: json (default), jsonl, csv, parquet, sqlite or
+//!                      auto (detected from the content); gzip-compressed input in any format is
+//!                      decompressed transparently
+//!   --query SQL        query selecting the record columns of an sqlite input
+//!   --input-shape S    shape of json input: array (default) or map (an object keyed by id)
+//!   --records-path P   dotted path to the records in a wrapping json document (e.g. data.items);
+//!                      also accepted as --input-array-path
+//!   --delimiter C      field delimiter for csv input (default ','; use '\t' for TSV)
+//!   --lenient          ignore trailing data after the JSON array instead of failing
+//!   --respect-preferred
+//!                      use a label marked `preferred: true` as the display name
+//!   --skip-if-unchanged
+//!                      skip conversion when the input hash matches the last run's
+//!   --strict-fields    fail on input keys the converter does not recognize
+//!   --strict-utf8      reject input with invalid UTF-8, reporting its byte offset, before parsing
+//!   --strict           fail when two affiliation entries share an identifier value
+//!   --record-filter E  (affiliations) only convert records matching the expression E, e.g.
+//!                      'country == "DE" && name contains "Universit"'; also accepted as --filter
+//!   --strip-html       remove HTML tags and decode HTML entities before sanitizing
+//!   --fold-diacritics-only
+//!                      only strip accents from Latin letters instead of transliterating to ASCII
+//!   --deunicode-errors P
+//!                      handling of untransliterable characters: strip, placeholder (default) or error
+//!   --id-scheme S      identifier scheme to emit instead of the vocabulary's default
+//!                      (affiliations: ror, names: orcid, funding: fundref, awards: other, subjects: mesh)
+//!   --preserve-scheme-case
+//!                      emit schemes as given instead of lowercasing them
+//!   --normalize-id-scheme-case
+//!                      lowercase emitted schemes (the default)
+//!   --strip-legal-suffixes
+//!                      drop legal suffixes such as "GmbH" or "Inc." from the display name
+//!   --emit-country-name
+//!                      add the English country name for the record's ISO 3166 country code
+//!   --identifier-key K key for the identifiers list: identifiers (default) or e.g. identifier
+//!   --full-identifier  emit the full identifier URL instead of its last path segment
+//!   --identifier-base URL
+//!                      rebuild full identifiers as URL + the extracted segment
+//!   --generate-ids     derive a slug id from the name for records without an id
+//!   --seed N           with --generate-ids, suffix colliding ids with a hash seeded by N
+//!   --entry-id-prefix P
+//!                      prepend P to every emitted id (identifier values are unchanged)
+//!   --add-title L=V    insert the constant title V under key L into every entry (repeatable)
+//!   --lang-allowlist L comma-separated title languages to keep (the primary en title is always kept)
+//!   --dedupe-titles    drop titles in other languages that repeat the primary en title
+//!   --collapse-dialects
+//!                      merge dialect labels (pt-BR, pt-PT) into one base-language title (pt)
+//!   --include-labels-from PATH
+//!                      merge titles from a JSON file of { id: { lang: label } } (local labels win)
+//!   --overrides PATH   (affiliations) replace entry fields with the corrections in a JSON file of
+//!                      { id: { field: value } }, e.g. { "00aaa1234": { "name": "..." } }
+//!   --on-empty-name P  disposition of records with an empty name: skip, keep (default) or error
+//!   --name-from-id-fallback
+//!                      name records with no name and no labels after their id segment
+//!   --split-acronyms   split acronyms joined by commas or semicolons ("MIT, M.I.T.")
+//!   --redact F         (names) replace F (given_name or family_name) with a placeholder; repeatable
+//!   --name-format F    (names) compose name as family-comma-given (default) or given-family
+//!   --all-acronyms     also emit every distinct acronym (case-insensitively deduplicated);
+//!                      same as --acronym-strategy all
+//!   --acronym-strategy S
+//!                      acronym fields to emit: first (default), all, title-variants or all-and-variants
+//!   --acronym-pick P   primary acronym among several: first (default), shortest or longest
+//!   --max-acronym-length N
+//!                      drop acronyms longer than N characters with a warning (default 30)
+//!   --max-description-length N
+//!                      truncate award descriptions to N characters
+//!   --alphabetize-acronyms
+//!                      sort the emitted acronyms list lexicographically
+//!   --format F         output format: yaml (default) or jsonl (one JSON object per line, no BOM)
+//!   --compact-yaml     write each entry on a single line as a YAML flow mapping
+//!   --tag TAG          attach TAG to every entry's tags; repeatable
+//!   --tags-from-types  also tag affiliations with their lowercased record types (e.g. education)
+//!   --output-newline N line ending of the written YAML: lf (default) or crlf
+//!   --use-anchors      (experimental) write repeated identifier schemes as YAML anchors and aliases
+//!   --fields-include F comma-separated list of entry fields to emit (e.g. id,name)
+//!   --fields-exclude F comma-separated list of entry fields to omit
+//!   --vocab-version V  record the source snapshot version V in a header comment
+//!   --version-in-props also record the version in each entry's props
+//!   --baseline PATH    (affiliations) only emit entries that are new or changed versus this
+//!                      YAML file
+//!   --skos             emit subjects as SKOS-style concepts (prefLabel, broader, narrower)
+//!   --output-mode M    set the output file's permissions to the octal mode M (Unix only)
+//!   --verify-output    re-read the written output and check it parses with the expected count
+//!   --checkpoint PATH  (affiliations) record written records in PATH and resume an interrupted
+//!                      write from it, appending to the existing output
+//!   --golden PATH      fail unless every entry has all the keys of the golden entry in PATH
+//!   --compare-with-registry
+//!                      (affiliations) warn about ROR ids the registry does not know
+//!   --registry-url URL registry queried by --compare-with-registry as URL/ID
+//!                      (default https://api.ror.org/v2/organizations)
+//!   --registry-sample N
+//!                      only look up N ids, spread evenly over the entries
+//!   --registry-rate N  send at most N registry requests per second (default 2; 0 for no limit)
+//!   --emit-lookup PATH write a compact { id: name } JSON object for the emitted entries
+//!   --lang-stats PATH  (affiliations) write a table of how many entries have a title in each
+//!                      language
+//!   --report-duplicates PATH
+//!                      (names) write a table of the input records merged away by deduplication
+//!   --metrics-file PATH
+//!                      write conversion metrics in Prometheus textfile format
+//!   --overwrite        replace an existing output without asking
+//!   --create-dirs      create the output file's missing parent directories
+//!   --tee PATH         also write a byte-identical copy of the output to PATH
+//!   --fixtures-dir DIR write DIR/vocabularies/<VOCAB_TYPE>.yaml and register it in
+//!                      DIR/vocabularies.yaml (OUTPUT_YAML is then omitted)
+//!   --max-errors N     with validate, stop after N invalid records (default 10)
+//!   --manifest-run PATH
+//!                      run every conversion listed in a TOML manifest, then print a combined summary
+//!   --fail-fast        in batch runs, stop at the first failed conversion (default)
+//!   --continue         in batch runs, convert the remaining entries and report failures at the end
+//!   --dump-schema T    print the JSON Schema of the input expected for vocabulary type T and exit
+//!   --profile          print the time spent reading, parsing, transforming and writing
+//!   --watch            re-convert whenever the input file changes, until interrupted
+//!   --preview N        print the first N converted entries to stdout instead of writing OUTPUT_YAML
+//!   --explain          describe the configured input, filters, transformations and output,
+//!                      then exit without converting
+//!   --count            print how many entries the conversion would write (after filtering,
+//!                      skipping and merging records) and exit without converting; not
+//!                      combinable with --baseline
+//!
+//! Exit codes:
+//!   0  success
+//!   2  usage error
+//!   3  unknown or unsupported vocabulary type
+//!   4  file read/write error
+//!   5  input parse error
+//!   6  validation failure
 //!
 //! Example:
 //!   vocab_converter affiliations ./input.json ./output.yaml
 
 use std::env;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::ops::ControlFlow;
+use std::path::Path;
 use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-mod vocab {
-    /// Module for converting an Affiliations vocabulary.
-    pub mod affiliations {
-        use deunicode::deunicode;
-        use serde::{Deserialize, Serialize};
-        use std::collections::HashMap;
-        use std::error::Error;
-        use std::fs::File;
-        use std::io::{BufReader, BufWriter, Write};
-        use std::path::Path;
-
-        #[derive(Debug, Deserialize)]
-        pub struct AffiliationItem {
-            #[serde(deserialize_with = "deserialize_null_default")]
-            pub id: String,
-            #[serde(deserialize_with = "deserialize_null_default")]
-            pub name: String,
-            #[serde(default)]
-            pub labels: Vec<Label>,
-            #[serde(default)]
-            pub acronyms: Vec<String>,
-        }
-
-        #[derive(Debug, Deserialize)]
-        pub struct Label {
-            #[serde(deserialize_with = "deserialize_null_default")]
-            pub iso639: String,
-            #[serde(deserialize_with = "deserialize_null_default")]
-            pub label: String,
-        }
-
-        pub fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
-        where
-            T: Default + Deserialize<'de>,
-            D: serde::Deserializer<'de>,
-        {
-            let opt = Option::deserialize(deserializer)?;
-            Ok(opt.unwrap_or_default())
-        }
-
-        /// Sanitize a string by transliterating ambiguous Unicode characters (such as Cyrillic)
-        /// into their approximate ASCII equivalents.
-        fn sanitize(s: &str) -> String {
-            deunicode(s)
-        }
-
-        #[derive(Debug, Serialize)]
-        pub struct YamlEntry {
-            pub id: String,
-            pub name: String,
-            pub title: HashMap<String, String>,
-            pub identifiers: Vec<Identifier>,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            pub acronym: Option<String>,
-        }
-
-        #[derive(Debug, Serialize)]
-        pub struct Identifier {
-            pub identifier: String,
-            pub scheme: String,
-        }
-
-        /// Convert a JSON file containing Affiliations data into a YAML file.
-        /// This function sanitizes all strings to replace ambiguous characters.
-        pub fn convert_json_to_yaml(json_path: &Path, yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            // Open and deserialize the JSON file.
-            let file = File::open(json_path)?;
-            let reader = BufReader::new(file);
-            let items: Vec<AffiliationItem> = serde_json::from_reader(reader)?;
-
-            let mut yaml_data = Vec::new();
-
-            for item in items {
-                // Sanitize the id and extract the last segment.
-                let id_sanitized = sanitize(&item.id);
-                let id_part = id_sanitized.split('/').last().unwrap_or_default().to_string();
-
-                let mut title = std::collections::HashMap::new();
-                title.insert("en".to_string(), sanitize(&item.name));
-
-                // Process and sanitize any labels.
-                for label in &item.labels {
-                    if !label.iso639.is_empty() && !label.label.is_empty() {
-                        title.insert(sanitize(&label.iso639), sanitize(&label.label));
-                    }
-                }
+use invenio_vocb_converter::vocab::{self, ConversionReport, ParseError, UnsupportedError, ValidationError, Verbosity, VocabType, Warning};
+use invenio_vocb_converter::vocab::affiliations::{AcronymStrategy, ConvertOptions, FieldProjection};
+use serde::Serialize;
 
-                // Get the first non-empty acronym, if available.
-                let acronym = item.acronyms.iter()
-                    .find(|s| !s.is_empty())
-                    .map(|s| sanitize(s));
+/// Exit code for invalid command-line usage.
+const EXIT_USAGE: i32 = 2;
+/// Exit code for a vocabulary type that is unknown or not yet implemented.
+const EXIT_UNSUPPORTED: i32 = 3;
+/// Exit code for failures reading or writing files.
+const EXIT_IO: i32 = 4;
+/// Exit code for input that could not be parsed.
+const EXIT_PARSE: i32 = 5;
+/// Exit code for records or output that failed validation.
+const EXIT_VALIDATION: i32 = 6;
 
-                let identifier = Identifier {
-                    identifier: id_part.clone(),
-                    scheme: "affiliation".to_string(),
-                };
+/// Invalid records reported by `validate` before it stops, unless `--max-errors` is given.
+const DEFAULT_MAX_ERRORS: usize = 10;
 
-                let yaml_entry = YamlEntry {
-                    id: id_part,
-                    name: sanitize(&item.name),
-                    title,
-                    identifiers: vec![identifier],
-                    acronym,
-                };
+/// How long `--watch` waits for a burst of changes to settle, and the polling interval where
+/// file notifications are unavailable.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
-                yaml_data.push(yaml_entry);
-            }
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [OPTIONS] <VOCAB_TYPE> <INPUT_JSON> <OUTPUT_YAML>",
+        program
+    );
+    eprintln!("       {} --fixtures-dir DIR [OPTIONS] <VOCAB_TYPE> <INPUT_JSON>", program);
+    eprintln!("       {} --dump-schema <VOCAB_TYPE>", program);
+    eprintln!("       {} [OPTIONS] --manifest-run <MANIFEST_TOML>", program);
+    eprintln!("       {} [--max-errors N] [--strict-fields] validate <VOCAB_TYPE> <INPUT_JSON>", program);
+    eprintln!("       {} changelog <OLD_YAML> <NEW_YAML>", program);
+    eprintln!("VOCAB_TYPE must be one of: affiliations, names, funding, awards, subjects");
+    process::exit(EXIT_USAGE);
+}
 
-            // Create file and wrap with BufWriter.
-            let file = File::create(yaml_path)?;
-            let mut writer = BufWriter::new(file);
+/// Map an error to the exit code of its category.
+fn exit_code(err: &(dyn Error + 'static)) -> i32 {
+    if let Some(err) = err.downcast_ref::<BatchError>() {
+        err.code
+    } else if err.is::<ValidationError>() {
+        EXIT_VALIDATION
+    } else if err.is::<UnsupportedError>() {
+        EXIT_UNSUPPORTED
+    } else if err.is::<ParseError>() || err.is::<serde_yaml::Error>() {
+        EXIT_PARSE
+    } else if let Some(err) = err.downcast_ref::<serde_json::Error>() {
+        if err.is_io() { EXIT_IO } else { EXIT_PARSE }
+    } else if err.is::<std::io::Error>() {
+        EXIT_IO
+    } else {
+        1
+    }
+}
 
-            // Optionally, write the UTF-8 BOM to ensure proper encoding detection.
-            writer.write_all(b"\xEF\xBB\xBF")?;
+/// Take the value following `flag`, exiting with usage if it is missing.
+fn flag_value<'a>(iter: &mut impl Iterator<Item = &'a String>, program: &str, flag: &str) -> &'a str {
+    match iter.next() {
+        Some(value) => value,
+        None => {
+            eprintln!("Missing value for {}", flag);
+            usage(program);
+        }
+    }
+}
 
-            // Serialize the data to YAML.
-            serde_yaml::to_writer(&mut writer, &yaml_data)?;
+/// Parse the value following `flag`, exiting with usage if it is missing or invalid.
+fn parse_flag<'a, T>(iter: &mut impl Iterator<Item = &'a String>, program: &str, flag: &str) -> T
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let value = flag_value(iter, program, flag);
+    value.parse().unwrap_or_else(|err| {
+        eprintln!("Invalid value for {}: {}", flag, err);
+        usage(program);
+    })
+}
 
-            Ok(())
-        }
-    } // end of affiliations module
+/// Print the first `count` entries as YAML to stdout.
+fn print_preview<T: Serialize>(entries: &[T], count: usize, options: &ConvertOptions) -> Result<(), Box<dyn Error>> {
+    let shown = &entries[..count.min(entries.len())];
+    print!("{}", vocab::affiliations::entries_to_yaml(shown, options)?);
+    Ok(())
+}
+
+/// Refuse to replace an existing output unless the user confirms it on a terminal.
+///
+/// Without a TTY on stdin there is nobody to ask, so the conversion fails instead.
+fn confirm_overwrite(yaml_path: &Path) -> Result<(), Box<dyn Error>> {
+    let size = match std::fs::metadata(yaml_path) {
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => return Ok(()),
+    };
+
+    let refused = || {
+        io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is {} bytes; pass --overwrite to replace it", yaml_path.display(), size),
+        )
+    };
 
-    // Placeholder modules for future controlled vocabularies.
+    if !io::stdin().is_terminal() {
+        return Err(refused().into());
+    }
 
-    pub mod names {
-        use std::error::Error;
-        use std::path::Path;
+    eprint!("{} exists and is {} bytes; overwrite? [y/N] ", yaml_path.display(), size);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if matches!(answer.trim(), "y" | "Y" | "yes") {
+        Ok(())
+    } else {
+        Err(refused().into())
+    }
+}
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Names vocabulary conversion not yet implemented.".into())
+/// Stream-validate `json_path` as input for `vocab_type`, printing each invalid record.
+fn validate(vocab_type: VocabType, json_path: &Path, options: &ConvertOptions, max_errors: usize) -> Result<(), Box<dyn Error>> {
+    let reader = io::BufReader::new(std::fs::File::open(json_path)?);
+    let errors = match vocab_type {
+        VocabType::Affiliations => vocab::affiliations::validate_stream(reader, options, max_errors),
+        VocabType::Names => vocab::validate_stream::<vocab::names::NameItem, _>(reader, max_errors),
+        VocabType::Subjects => vocab::validate_stream::<vocab::subjects::SubjectItem, _>(reader, max_errors),
+        VocabType::Awards => vocab::validate_stream::<vocab::awards::AwardItem, _>(reader, max_errors),
+        VocabType::Funding => {
+            return Err(UnsupportedError(format!("{} vocabulary conversion not yet implemented.", vocab_type)).into());
         }
+    };
+
+    if errors.is_empty() {
+        options.log(Verbosity::Normal, &format!("{} is valid {} input", json_path.display(), vocab_type));
+        return Ok(());
+    }
+    for error in &errors {
+        eprintln!("Invalid {}", error);
     }
+    let stopped = if errors.len() >= max_errors { " (stopped at --max-errors)" } else { "" };
+    Err(ValidationError(format!("{} invalid records in {}{}", errors.len(), json_path.display(), stopped)).into())
+}
 
-    pub mod funding {
-        use std::error::Error;
-        use std::path::Path;
+/// Check `entries` against the `--golden` entry shape, if one was given.
+fn check_golden<T: Serialize>(cli: &CliSettings, entries: &[T], options: &ConvertOptions) -> Result<(), Box<dyn Error>> {
+    match &cli.golden {
+        Some(golden_path) => {
+            let golden = vocab::affiliations::read_golden(Path::new(golden_path))?;
+            vocab::affiliations::check_golden(&golden, entries, options)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Count the entries converting `json_path` would write, without converting or writing them.
+///
+/// Affiliations and names apply their filter, skip and merge rules record by record; awards and
+/// subjects convert every record into exactly one entry.
+fn count(vocab_type: VocabType, json_path: &Path, options: &ConvertOptions) -> Result<usize, Box<dyn Error>> {
+    let reader = io::BufReader::new(std::fs::File::open(json_path)?);
+    match vocab_type {
+        VocabType::Affiliations => vocab::affiliations::count_items_from(reader, options),
+        VocabType::Names => vocab::names::count_items_from(reader, options),
+        VocabType::Subjects => Ok(vocab::subjects::read_items_from(reader, options)?.len()),
+        VocabType::Awards => Ok(vocab::awards::read_items_from(reader, options)?.len()),
+        VocabType::Funding => Err(UnsupportedError(format!("{} vocabulary conversion not yet implemented.", vocab_type)).into()),
+    }
+}
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Funding vocabulary conversion not yet implemented.".into())
+/// Describe, one line per step, the pipeline that converting `json_path` into `yaml_path` would run.
+fn explain(vocab_type: VocabType, json_path: &Path, yaml_path: &Path, options: &ConvertOptions, cli: &CliSettings) -> Vec<String> {
+    use vocab::affiliations::{
+        AcronymPick, DeunicodeErrors, EmptyNamePolicy, InputFormat, InputShape, NameFormat, Newline, OutputFormat,
+        DEFAULT_SQLITE_QUERY,
+    };
+
+    let mut lines = vec![format!("Convert {} {} into {}", vocab_type, json_path.display(), yaml_path.display())];
+    let mut section = |title: &str, steps: Vec<String>| {
+        lines.push(format!("{}:", title));
+        if steps.is_empty() {
+            lines.push("  - none".to_string());
         }
+        lines.extend(steps.into_iter().map(|step| format!("  - {}", step)));
+    };
+
+    let mut input = vec![match options.input_format {
+        InputFormat::Auto => "detect the format (JSON, JSON Lines, CSV, Parquet or SQLite) from the content".to_string(),
+        InputFormat::Json => "read a JSON document".to_string(),
+        InputFormat::JsonLines => "read JSON Lines, one record per line".to_string(),
+        InputFormat::Csv => format!("read CSV with delimiter {:?}", options.delimiter),
+        InputFormat::Parquet => "read Parquet rows".to_string(),
+        InputFormat::Sqlite => format!(
+            "read SQLite rows selected by {:?}",
+            options.sqlite_query.as_deref().unwrap_or(DEFAULT_SQLITE_QUERY)
+        ),
+    }];
+    // An unreadable input can still be recognized as gzip by its extension.
+    let gzip = vocab::gzip::is_gzip_file(json_path)
+        .unwrap_or_else(|_| json_path.extension().is_some_and(|extension| extension == "gz"));
+    if gzip {
+        input.push("decompress gzip input transparently".to_string());
+    }
+    if options.input_shape == InputShape::Map {
+        input.push("read records from an object keyed by id".to_string());
+    }
+    if let Some(path) = &options.records_path {
+        input.push(format!("take the records from '{}'", path));
+    }
+    if options.lenient {
+        input.push("ignore trailing data after the JSON array".to_string());
+    }
+    if options.strict_utf8 {
+        input.push("reject invalid UTF-8 before parsing".to_string());
+    }
+    if options.strict_fields {
+        input.push("fail on unknown input keys".to_string());
+    }
+    section("Input", input);
+
+    let mut filters = Vec::new();
+    match options.on_empty_name {
+        EmptyNamePolicy::Skip => filters.push("skip records with an empty name".to_string()),
+        EmptyNamePolicy::Error => filters.push("fail on records with an empty name".to_string()),
+        EmptyNamePolicy::Keep => {}
+    }
+    if let Some(langs) = &options.lang_allowlist {
+        filters.push(format!("keep only titles in en, {}", langs.join(", ")));
+    }
+    if options.dedupe_titles {
+        filters.push("drop titles repeating the en title".to_string());
+    }
+    if options.max_acronym_length != vocab::affiliations::DEFAULT_MAX_ACRONYM_LENGTH {
+        filters.push(format!("drop acronyms longer than {} characters", options.max_acronym_length));
+    }
+    match &options.fields {
+        FieldProjection::All => {}
+        FieldProjection::Include(fields) => filters.push(format!("emit only the fields {}", fields.join(", "))),
+        FieldProjection::Exclude(fields) => filters.push(format!("omit the fields {}", fields.join(", "))),
+    }
+    if options.record_filter.is_some() {
+        filters.push("convert only records matching the record filter".to_string());
+    }
+    if let Some(baseline) = &cli.baseline {
+        filters.push(format!("emit only entries changed since {}", baseline));
+    }
+    if let Some(count) = cli.preview {
+        filters.push(format!("print the first {} entries instead of writing", count));
+    }
+    section("Filters", filters);
+
+    let mut steps = vec![match (options.fold_diacritics_only, options.deunicode_errors) {
+        (true, _) => "strip accents from Latin letters".to_string(),
+        (false, DeunicodeErrors::Placeholder) => "transliterate text to ASCII, marking untransliterable characters".to_string(),
+        (false, DeunicodeErrors::Strip) => "transliterate text to ASCII, dropping untransliterable characters".to_string(),
+        (false, DeunicodeErrors::Error) => "transliterate text to ASCII, failing on untransliterable characters".to_string(),
+    }];
+    if options.strip_html {
+        steps.insert(0, "strip HTML tags and entities".to_string());
     }
+    let scheme = options.scheme_for(vocab_type);
+    steps.push(format!("emit identifiers with scheme '{}'", scheme));
+    if let Some(prefix) = &options.entry_id_prefix {
+        steps.push(format!("prefix ids with '{}'", prefix));
+    }
+    if !options.overrides.is_empty() {
+        steps.push(format!("apply manual overrides to {} entries", options.overrides.len()));
+    }
+    if options.generate_ids {
+        steps.push("generate ids from names for records without one".to_string());
+    }
+    if options.collapse_dialects {
+        steps.push("merge dialect titles into their base language".to_string());
+    }
+    if options.strip_legal_suffixes {
+        steps.push("strip legal suffixes from names".to_string());
+    }
+    if options.acronym_pick != AcronymPick::First {
+        steps.push(format!("pick the {} acronym", if options.acronym_pick == AcronymPick::Shortest { "shortest" } else { "longest" }));
+    }
+    if options.acronym_strategy != AcronymStrategy::First {
+        steps.push("emit every distinct acronym".to_string());
+    }
+    if vocab_type == VocabType::Names {
+        let format = match options.name_format {
+            NameFormat::FamilyCommaGiven => "Family, Given",
+            NameFormat::GivenFamily => "Given Family",
+        };
+        steps.push(format!("compose names as \"{}\"", format));
+    }
+    if !options.redact.is_empty() {
+        steps.push(format!("redact {} name field(s)", options.redact.len()));
+    }
+    if !options.tags.is_empty() || options.tags_from_types {
+        steps.push("tag entries".to_string());
+    }
+    if options.skos && vocab_type == VocabType::Subjects {
+        steps.push("emit SKOS concepts".to_string());
+    }
+    section("Transformations", steps);
 
-    pub mod awards {
-        use std::error::Error;
-        use std::path::Path;
+    let format = match options.output_format {
+        OutputFormat::Yaml if options.compact_yaml => "compact YAML",
+        OutputFormat::Yaml => "block-style YAML",
+        OutputFormat::JsonLines => "JSON Lines",
+    };
+    let newline = if options.output_newline == Newline::Crlf { "CRLF" } else { "LF" };
+    let mut output = vec![format!("write {} with {} line endings", format, newline)];
+    if options.use_anchors && options.output_format == OutputFormat::Yaml {
+        output.push("anchor repeated schemes".to_string());
+    }
+    if let Some(tee) = &options.tee {
+        output.push(format!("also write a copy to {}", tee.display()));
+    }
+    if let Some(checkpoint) = &cli.checkpoint {
+        output.push(format!("checkpoint progress in {}", checkpoint));
+    }
+    if options.verify_output {
+        output.push("re-read and verify the output".to_string());
+    }
+    if let Some(golden) = &cli.golden {
+        output.push(format!("check entries against the golden shape in {}", golden));
+    }
+    if cli.compare_with_registry {
+        output.push(format!("look ROR ids up in {}", cli.registry.url));
+    }
+    section("Output", output);
+
+    lines
+}
+
+/// Write the `--emit-lookup` file for `entries`, if requested.
+fn write_lookup<T: Serialize>(cli: &CliSettings, entries: &[T]) -> Result<(), Box<dyn Error>> {
+    match &cli.emit_lookup {
+        Some(lookup_path) => vocab::affiliations::write_lookup(Path::new(lookup_path), entries),
+        None => Ok(()),
+    }
+}
+
+/// Pipeline stage names reported by `--profile`, in execution order.
+const STAGE_READ: &str = "read";
+const STAGE_PARSE: &str = "parse";
+const STAGE_TRANSFORM: &str = "transform";
+const STAGE_WRITE: &str = "write";
+
+/// Read the whole input at `json_path`, decompressing gzip data.
+fn read_input(json_path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let file = io::BufReader::new(std::fs::File::open(json_path)?);
+    vocab::gzip::with_decompressed(file, |reader| {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    })
+}
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Awards vocabulary conversion not yet implemented.".into())
+/// Wall-clock time spent in each pipeline stage, as printed by `--profile`.
+#[derive(Debug, Default)]
+struct Profile {
+    stages: Vec<(&'static str, Duration)>,
+}
+
+impl Profile {
+    /// Run `stage` and record how long it took.
+    fn time<T>(&mut self, name: &'static str, stage: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = stage();
+        self.stages.push((name, started.elapsed()));
+        result
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: Duration = self.stages.iter().map(|(_, elapsed)| *elapsed).sum();
+        writeln!(f, "Profile:")?;
+        for (name, elapsed) in &self.stages {
+            let share = if total.is_zero() { 0.0 } else { elapsed.as_secs_f64() / total.as_secs_f64() * 100.0 };
+            writeln!(f, "  {:<10} {:>10.3} ms {:>5.1}%", name, elapsed.as_secs_f64() * 1000.0, share)?;
         }
+        writeln!(f, "  {:<10} {:>10.3} ms", "total", total.as_secs_f64() * 1000.0)
+    }
+}
+
+/// Build the conversion report and log the summary line.
+fn summarize(
+    records_total: usize,
+    emitted_total: usize,
+    warnings: Vec<Warning>,
+    started: Instant,
+    yaml_path: &Path,
+    options: &ConvertOptions,
+) -> ConversionReport {
+    let report = ConversionReport {
+        records_total,
+        emitted_total,
+        skipped_total: records_total - emitted_total,
+        duration: started.elapsed(),
+        warnings,
+    };
+
+    options.log(
+        Verbosity::Normal,
+        &format!(
+            "Converted {} of {} records ({} skipped) to {}",
+            report.emitted_total,
+            report.records_total,
+            report.skipped_total,
+            yaml_path.display()
+        ),
+    );
+    report
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        process::exit(exit_code(err.as_ref()));
     }
+}
 
-    pub mod subjects {
-        use std::error::Error;
-        use std::path::Path;
+/// Settings that only affect how the CLI drives a conversion, not the conversion itself.
+#[derive(Debug, Clone, Default)]
+struct CliSettings {
+    preview: Option<usize>,
+    lang_stats: Option<String>,
+    emit_lookup: Option<String>,
+    checkpoint: Option<String>,
+    golden: Option<String>,
+    compare_with_registry: bool,
+    explain: bool,
+    count: bool,
+    registry: vocab::registry::Registry,
+    skip_if_unchanged: bool,
+    metrics_file: Option<String>,
+    report_duplicates: Option<String>,
+    baseline: Option<String>,
+    watch: bool,
+    overwrite: bool,
+    dump_schema: Option<String>,
+    fixtures_dir: Option<String>,
+    max_errors: Option<usize>,
+    profile: bool,
+    manifest_run: Option<String>,
+    continue_on_error: bool,
+}
+
+/// Apply the command-line flags in `args` to `options` and `cli`, returning the positional arguments.
+fn parse_args<'a>(
+    args: &'a [String],
+    program: &str,
+    options: &mut ConvertOptions,
+    cli: &mut CliSettings,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let mut positional = Vec::new();
 
-        #[allow(dead_code)]
-        pub fn convert_json_to_yaml(_json_path: &Path, _yaml_path: &Path) -> Result<(), Box<dyn Error>> {
-            Err("Subjects vocabulary conversion not yet implemented.".into())
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let flag = arg.as_str();
+        match flag {
+            "-q" | "--quiet" => options.verbosity = Verbosity::Quiet,
+            "-v" | "--verbose" => options.verbosity = Verbosity::Verbose,
+            "-vv" => options.verbosity = Verbosity::Debug,
+            "--trace-record" => {
+                options.trace_record = Some(flag_value(&mut iter, program, flag).to_string());
+                options.verbosity = Verbosity::Debug;
+            }
+            "--strip-html" => options.strip_html = true,
+            "--fold-diacritics-only" => options.fold_diacritics_only = true,
+            "--lenient" => options.lenient = true,
+            "--respect-preferred" => options.respect_preferred = true,
+            "--strict-fields" => options.strict_fields = true,
+            "--strict-utf8" => options.strict_utf8 = true,
+            "--strict" => options.strict = true,
+            "--compact-yaml" => options.compact_yaml = true,
+            "--format" => options.output_format = parse_flag(&mut iter, program, flag),
+            "--use-anchors" => options.use_anchors = true,
+            "--tag" => options.tags.push(flag_value(&mut iter, program, flag).to_string()),
+            "--tags-from-types" => options.tags_from_types = true,
+            "--output-newline" => options.output_newline = parse_flag(&mut iter, program, flag),
+            "--all-acronyms" => options.acronym_strategy = AcronymStrategy::All,
+            "--alphabetize-acronyms" => options.alphabetize_acronyms = true,
+            "--verify-output" => options.verify_output = true,
+            "--skos" => options.skos = true,
+            "--full-identifier" => options.full_identifier = true,
+            "--strip-legal-suffixes" => options.strip_legal_suffixes = true,
+            "--emit-country-name" => options.emit_country_name = true,
+            "--version-in-props" => options.version_in_props = true,
+            "--generate-ids" => options.generate_ids = true,
+            "--seed" => options.seed = Some(parse_flag(&mut iter, program, flag)),
+            "--skip-if-unchanged" => cli.skip_if_unchanged = true,
+            "--watch" => cli.watch = true,
+            "--overwrite" => cli.overwrite = true,
+            "--explain" => cli.explain = true,
+            "--count" => cli.count = true,
+            "--record-filter" | "--filter" => options.record_filter = Some(parse_flag(&mut iter, program, flag)),
+            "--create-dirs" => options.create_dirs = true,
+            "--tee" => options.tee = Some(flag_value(&mut iter, program, flag).into()),
+            "--profile" => cli.profile = true,
+            "--fail-fast" => cli.continue_on_error = false,
+            "--continue" => cli.continue_on_error = true,
+            "--manifest-run" => cli.manifest_run = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--input-format" => options.input_format = parse_flag(&mut iter, program, flag),
+            "--input-shape" => options.input_shape = parse_flag(&mut iter, program, flag),
+            "--records-path" | "--input-array-path" => options.records_path = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--query" => options.sqlite_query = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--delimiter" => {
+                let value = flag_value(&mut iter, program, flag);
+                options.delimiter = match value {
+                    "\\t" | "tab" => '\t',
+                    other => other.parse().unwrap_or_else(|_| {
+                        eprintln!("Invalid value for --delimiter: {}", value);
+                        usage(program);
+                    }),
+                };
+            }
+            "--deunicode-errors" => options.deunicode_errors = parse_flag(&mut iter, program, flag),
+            "--id-scheme" => options.id_scheme = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--preserve-scheme-case" => options.preserve_scheme_case = true,
+            "--normalize-id-scheme-case" => options.preserve_scheme_case = false,
+            "--add-title" => {
+                let value = flag_value(&mut iter, program, flag);
+                match value.split_once('=') {
+                    Some((lang, title)) if !lang.is_empty() => {
+                        options.extra_titles.push((lang.to_string(), title.to_string()));
+                    }
+                    _ => {
+                        eprintln!("Invalid value for --add-title: {} (expected lang=value)", value);
+                        usage(program);
+                    }
+                }
+            }
+            "--dedupe-titles" => options.dedupe_titles = true,
+            "--collapse-dialects" => options.collapse_dialects = true,
+            "--lang-allowlist" => {
+                let langs = flag_value(&mut iter, program, flag)
+                    .split(',')
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect();
+                options.lang_allowlist = Some(langs);
+            }
+            "--include-labels-from" => {
+                let path = flag_value(&mut iter, program, flag);
+                options.included_labels = vocab::affiliations::read_included_labels(Path::new(path))?;
+            }
+            "--overrides" => {
+                let path = flag_value(&mut iter, program, flag);
+                options.overrides = vocab::affiliations::read_overrides(Path::new(path))?;
+            }
+            "--identifier-key" => options.identifier_key = flag_value(&mut iter, program, flag).to_string(),
+            "--entry-id-prefix" => options.entry_id_prefix = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--identifier-base" => options.identifier_base = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--acronym-strategy" => options.acronym_strategy = parse_flag(&mut iter, program, flag),
+            "--acronym-pick" => options.acronym_pick = parse_flag(&mut iter, program, flag),
+            "--max-acronym-length" => options.max_acronym_length = parse_flag(&mut iter, program, flag),
+            "--max-description-length" => options.max_description_length = Some(parse_flag(&mut iter, program, flag)),
+            "--output-mode" => {
+                let value = flag_value(&mut iter, program, flag);
+                options.output_mode = Some(u32::from_str_radix(value, 8).unwrap_or_else(|_| {
+                    eprintln!("Invalid value for --output-mode: {} (expected an octal mode such as 0644)", value);
+                    usage(program);
+                }));
+            }
+            "--on-empty-name" => options.on_empty_name = parse_flag(&mut iter, program, flag),
+            "--name-from-id-fallback" => options.name_from_id_fallback = true,
+            "--split-acronyms" => options.split_acronyms = true,
+            "--redact" => options.redact.push(parse_flag(&mut iter, program, flag)),
+            "--name-format" => options.name_format = parse_flag(&mut iter, program, flag),
+            "--fields-include" | "--fields-exclude" => {
+                let fields = flag_value(&mut iter, program, flag)
+                    .split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect();
+                options.fields = if flag == "--fields-include" {
+                    FieldProjection::Include(fields)
+                } else {
+                    FieldProjection::Exclude(fields)
+                };
+            }
+            "--vocab-version" => options.vocab_version = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--baseline" => cli.baseline = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--emit-lookup" => cli.emit_lookup = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--checkpoint" => cli.checkpoint = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--golden" => cli.golden = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--compare-with-registry" => cli.compare_with_registry = true,
+            "--registry-url" => cli.registry.url = flag_value(&mut iter, program, flag).to_string(),
+            "--registry-sample" => cli.registry.sample = Some(parse_flag(&mut iter, program, flag)),
+            "--registry-rate" => cli.registry.requests_per_second = parse_flag(&mut iter, program, flag),
+            "--lang-stats" => cli.lang_stats = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--report-duplicates" => cli.report_duplicates = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--metrics-file" => cli.metrics_file = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--fixtures-dir" => cli.fixtures_dir = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--max-errors" => cli.max_errors = Some(parse_flag(&mut iter, program, flag)),
+            "--dump-schema" => cli.dump_schema = Some(flag_value(&mut iter, program, flag).to_string()),
+            "--preview" => cli.preview = Some(parse_flag(&mut iter, program, flag)),
+            _ if flag.starts_with('-') => {
+                eprintln!("Unknown option: {}", flag);
+                usage(program);
+            }
+            _ => positional.push(flag),
         }
     }
+
+    Ok(positional)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn run() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 4 {
-        eprintln!(
-            "Usage: {} <VOCAB_TYPE> <INPUT_JSON> <OUTPUT_YAML>",
-            args[0]
-        );
-        eprintln!("VOCAB_TYPE must be one of: affiliations, names, funding, awards, subjects");
-        process::exit(1);
+    let program = args[0].as_str();
+    let mut options = ConvertOptions::default();
+    let mut cli = CliSettings::default();
+    let positional = parse_args(&args[1..], program, &mut options, &mut cli)?;
+
+    let parse_vocab_type = |name: &str| -> VocabType {
+        name.parse().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(EXIT_UNSUPPORTED);
+        })
+    };
+
+    if let Some(name) = &cli.dump_schema {
+        let vocab_type = parse_vocab_type(name);
+        let Some(schema) = vocab::input_schema(vocab_type) else {
+            eprintln!("No input schema for {}: conversion not yet implemented.", vocab_type);
+            process::exit(EXIT_UNSUPPORTED);
+        };
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
     }
 
-    let vocab_type = &args[1].to_lowercase(); // normalize to lowercase
-    let json_path = std::path::Path::new(&args[2]);
-    let yaml_path = std::path::Path::new(&args[3]);
+    if let Some(manifest_path) = &cli.manifest_run {
+        if !positional.is_empty() {
+            usage(program);
+        }
+        return run_manifest(Path::new(manifest_path), program, &options, &cli);
+    }
 
-    match vocab_type.as_str() {
-        "affiliations" => {
-            vocab::affiliations::convert_json_to_yaml(json_path, yaml_path)?;
+    if positional.first() == Some(&"changelog") {
+        if positional.len() != 3 {
+            usage(program);
         }
-        "names" => {
-            eprintln!("Names vocabulary conversion not yet implemented.");
-            process::exit(1);
+        let old = vocab::affiliations::read_yaml_values(Path::new(positional[1]))?;
+        let new = vocab::affiliations::read_yaml_values(Path::new(positional[2]))?;
+        print!("{}", vocab::affiliations::changelog(&old, &new));
+        return Ok(());
+    }
+
+    if positional.first() == Some(&"validate") {
+        if positional.len() != 3 {
+            usage(program);
         }
-        "funding" => {
-            eprintln!("Funding vocabulary conversion not yet implemented.");
-            process::exit(1);
+        let vocab_type = parse_vocab_type(positional[1]);
+        return validate(vocab_type, Path::new(positional[2]), &options, cli.max_errors.unwrap_or(DEFAULT_MAX_ERRORS));
+    }
+
+    let expected_positional = if cli.fixtures_dir.is_some() { 2 } else { 3 };
+    if positional.len() != expected_positional {
+        usage(program);
+    }
+
+    let vocab_type = parse_vocab_type(positional[0]);
+    let json_path = std::path::Path::new(positional[1]);
+    let fixtures_path;
+    let yaml_path = match &cli.fixtures_dir {
+        Some(dir) => {
+            fixtures_path = vocab::fixtures_data_path(Path::new(dir), vocab_type);
+            if let Some(parent) = fixtures_path.parent().filter(|_| !cli.explain) {
+                std::fs::create_dir_all(parent)?;
+            }
+            fixtures_path.as_path()
         }
-        "awards" => {
-            eprintln!("Awards vocabulary conversion not yet implemented.");
-            process::exit(1);
+        None => std::path::Path::new(positional[2]),
+    };
+
+    if cli.explain {
+        for line in explain(vocab_type, json_path, yaml_path, &options, &cli) {
+            println!("{}", line);
         }
-        "subjects" => {
-            eprintln!("Subjects vocabulary conversion not yet implemented.");
-            process::exit(1);
+        return Ok(());
+    }
+
+    if cli.count {
+        // The delta against a baseline is only known once every entry is converted.
+        if cli.baseline.is_some() {
+            return Err(UnsupportedError("--count cannot be combined with --baseline".to_string()).into());
         }
-        _ => {
-            eprintln!("Unknown vocabulary type: {}", vocab_type);
-            process::exit(1);
+        println!("{}", count(vocab_type, json_path, &options)?);
+        return Ok(());
+    }
+
+    if cli.watch {
+        // Ask once up front; later cycles replace the output this run wrote itself.
+        if !cli.overwrite && cli.preview.is_none() {
+            confirm_overwrite(yaml_path)?;
+            cli.overwrite = true;
         }
+        // Keep watching after a failed cycle; the next edit may fix the input.
+        vocab::watch_file(json_path, WATCH_INTERVAL, || {
+            if let Err(err) = convert(vocab_type, json_path, yaml_path, &options, &cli) {
+                eprintln!("Error: {}", err);
+            }
+            ControlFlow::Continue(())
+        })?;
+        return Ok(());
     }
 
-    Ok(())
+    convert(vocab_type, json_path, yaml_path, &options, &cli).map(|_| ())
+}
+
+/// Run every conversion listed in the manifest at `manifest_path` and print a combined summary.
+///
+/// Each entry starts from the options given on the command line, then applies its own. With
+/// `--continue`, a failed entry is reported and the remaining ones still run; the run then
+/// fails with the exit code of the first failure.
+fn run_manifest(manifest_path: &Path, program: &str, options: &ConvertOptions, cli: &CliSettings) -> Result<(), Box<dyn Error>> {
+    let entries = vocab::manifest::read_manifest(manifest_path)?;
+    let mut converted = 0;
+    let (mut records_total, mut emitted_total) = (0, 0);
+    let mut failures: Vec<(String, Box<dyn Error>)> = Vec::new();
+
+    for entry in &entries {
+        let mut entry_options = options.clone();
+        let mut entry_cli = cli.clone();
+        entry_cli.manifest_run = None;
+        let extra = parse_args(&entry.options, program, &mut entry_options, &mut entry_cli)?;
+        if !extra.is_empty() {
+            eprintln!("Unexpected arguments in manifest options for {}: {}", entry.vocab_type, extra.join(" "));
+            usage(program);
+        }
+
+        match convert(entry.vocab_type, &entry.input, &entry.output, &entry_options, &entry_cli) {
+            Ok(report) => {
+                converted += 1;
+                if let Some(report) = report {
+                    records_total += report.records_total;
+                    emitted_total += report.emitted_total;
+                }
+            }
+            Err(err) if cli.continue_on_error => {
+                let label = format!("{} ({})", entry.vocab_type, entry.input.display());
+                eprintln!("Error: {}: {}", label, err);
+                failures.push((label, err));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    options.log(
+        Verbosity::Normal,
+        &format!(
+            "Manifest {}: converted {} of {} vocabularies ({} of {} records emitted)",
+            manifest_path.display(),
+            converted,
+            entries.len(),
+            emitted_total,
+            records_total
+        ),
+    );
+
+    let Some((_, first)) = failures.first() else {
+        return Ok(());
+    };
+    let failed: Vec<&str> = failures.iter().map(|(label, _)| label.as_str()).collect();
+    Err(BatchError {
+        message: format!("{} of {} conversions failed: {}", failures.len(), entries.len(), failed.join(", ")),
+        code: exit_code(first.as_ref()),
+    }
+    .into())
+}
+
+/// Failures collected by a batch run that kept going past them with `--continue`.
+#[derive(Debug)]
+struct BatchError {
+    message: String,
+    /// Exit code of the first failure.
+    code: i32,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for BatchError {}
+
+/// Run one conversion of `json_path` into `yaml_path`, including the CLI-level extras.
+fn convert(
+    vocab_type: VocabType,
+    json_path: &Path,
+    yaml_path: &Path,
+    options: &ConvertOptions,
+    cli: &CliSettings,
+) -> Result<Option<ConversionReport>, Box<dyn Error>> {
+    let input_hash = if cli.skip_if_unchanged && cli.preview.is_none() {
+        let hash = vocab::file_hash(json_path)?;
+        if vocab::is_unchanged(yaml_path, &hash) {
+            println!("Input unchanged since last run; skipping conversion.");
+            return Ok(None);
+        }
+        Some(hash)
+    } else {
+        None
+    };
+
+    // A resumed write appends to the output rather than replacing it.
+    let resuming = cli.checkpoint.as_deref().is_some_and(|path| Path::new(path).exists());
+    if !cli.overwrite && cli.preview.is_none() && !resuming {
+        confirm_overwrite(yaml_path)?;
+    }
+    if cli.checkpoint.is_some() && (vocab_type != VocabType::Affiliations || cli.baseline.is_some()) {
+        return Err(UnsupportedError("--checkpoint only supports affiliations conversions without --baseline".to_string()).into());
+    }
+    if options.record_filter.is_some() && vocab_type != VocabType::Affiliations {
+        return Err(UnsupportedError("--record-filter only supports affiliations conversions".to_string()).into());
+    }
+    if !options.overrides.is_empty() && vocab_type != VocabType::Affiliations {
+        return Err(UnsupportedError("--overrides only supports affiliations conversions".to_string()).into());
+    }
+    if cli.compare_with_registry && vocab_type != VocabType::Affiliations {
+        return Err(UnsupportedError("--compare-with-registry only supports affiliations conversions".to_string()).into());
+    }
+    if cli.baseline.is_some() && vocab_type != VocabType::Affiliations {
+        return Err(UnsupportedError("--baseline only supports affiliations conversions".to_string()).into());
+    }
+    if cli.lang_stats.is_some() && vocab_type != VocabType::Affiliations {
+        return Err(UnsupportedError("--lang-stats only supports affiliations conversions".to_string()).into());
+    }
+    if cli.report_duplicates.is_some() && vocab_type != VocabType::Names {
+        return Err(UnsupportedError("--report-duplicates only supports names conversions".to_string()).into());
+    }
+
+    let started = Instant::now();
+    let mut profile = Profile::default();
+
+    // Profiling reads and decompresses the whole input up front so the read and parse stages are
+    // timed apart; otherwise the input is streamed into the parser.
+    let input: Box<dyn io::Read> = if cli.profile {
+        Box::new(io::Cursor::new(profile.time(STAGE_READ, || read_input(json_path))?))
+    } else {
+        Box::new(io::BufReader::new(std::fs::File::open(json_path)?))
+    };
+
+    let report = match vocab_type {
+        VocabType::Affiliations => {
+            let items = profile.time(STAGE_PARSE, || vocab::affiliations::read_items_from(input, options))?;
+            let (mut entries, records, mut warnings) =
+                profile.time(STAGE_TRANSFORM, || vocab::affiliations::convert_items_with_records(&items, options))?;
+
+            if cli.compare_with_registry && cli.preview.is_none() {
+                let registry = &cli.registry;
+                warnings.extend(vocab::affiliations::compare_with_registry(&entries, &records, &items, registry, options)?);
+            }
+
+            if let Some(baseline_path) = &cli.baseline {
+                let previous = vocab::affiliations::read_yaml(std::path::Path::new(baseline_path))?;
+                entries = vocab::affiliations::changed_entries(entries, &previous);
+            }
+
+            if let Some(count) = cli.preview {
+                return print_preview(&entries, count, options).map(|()| None);
+            }
+
+            check_golden(cli, &entries, options)?;
+            match &cli.checkpoint {
+                Some(checkpoint_path) => {
+                    let resumed_from = profile.time(STAGE_WRITE, || {
+                        let checkpoint_path = Path::new(checkpoint_path);
+                        vocab::affiliations::write_yaml_with_checkpoint(yaml_path, &entries, &records, items.len(), checkpoint_path, options)
+                    })?;
+                    if resumed_from > 0 {
+                        options.log(Verbosity::Normal, &format!("Resumed after record {} from {}", resumed_from, checkpoint_path));
+                    }
+                }
+                None => profile.time(STAGE_WRITE, || vocab::affiliations::write_yaml_with_options(yaml_path, &entries, options))?,
+            }
+            write_lookup(cli, &entries)?;
+
+            if let Some(stats_path) = &cli.lang_stats {
+                vocab::affiliations::write_language_stats(std::path::Path::new(stats_path), &entries)?;
+            }
+
+            summarize(items.len(), entries.len(), warnings, started, yaml_path, options)
+        }
+        VocabType::Names => {
+            let items = profile.time(STAGE_PARSE, || vocab::names::read_items_from(input, options))?;
+            let (entries, duplicates) =
+                profile.time(STAGE_TRANSFORM, || vocab::names::convert_items_with_duplicates(&items, options))?;
+
+            if let Some(count) = cli.preview {
+                return print_preview(&entries, count, options).map(|()| None);
+            }
+
+            check_golden(cli, &entries, options)?;
+            profile.time(STAGE_WRITE, || vocab::affiliations::write_yaml_with_options(yaml_path, &entries, options))?;
+            write_lookup(cli, &entries)?;
+
+            if let Some(report_path) = &cli.report_duplicates {
+                vocab::names::write_duplicates_report(Path::new(report_path), &duplicates)?;
+            }
+            summarize(items.len(), entries.len(), Vec::new(), started, yaml_path, options)
+        }
+        VocabType::Funding => {
+            return Err(UnsupportedError(format!("{} vocabulary conversion not yet implemented.", vocab_type)).into());
+        }
+        VocabType::Awards => {
+            let items = profile.time(STAGE_PARSE, || vocab::awards::read_items_from(input, options))?;
+            let entries: Vec<_> = profile.time(STAGE_TRANSFORM, || {
+                items.iter().map(|item| vocab::awards::convert_item(item, options)).collect()
+            });
+
+            if let Some(count) = cli.preview {
+                return print_preview(&entries, count, options).map(|()| None);
+            }
+
+            check_golden(cli, &entries, options)?;
+            profile.time(STAGE_WRITE, || vocab::affiliations::write_yaml_with_options(yaml_path, &entries, options))?;
+            write_lookup(cli, &entries)?;
+            summarize(items.len(), entries.len(), Vec::new(), started, yaml_path, options)
+        }
+        VocabType::Subjects => {
+            let items = profile.time(STAGE_PARSE, || vocab::subjects::read_items_from(input, options))?;
+
+            if options.skos {
+                let entries: Vec<_> = profile.time(STAGE_TRANSFORM, || {
+                    items.iter().map(|item| vocab::subjects::convert_item_skos(item, options)).collect()
+                });
+                if let Some(count) = cli.preview {
+                    return print_preview(&entries, count, options).map(|()| None);
+                }
+                check_golden(cli, &entries, options)?;
+                profile.time(STAGE_WRITE, || vocab::affiliations::write_yaml_with_options(yaml_path, &entries, options))?;
+                write_lookup(cli, &entries)?;
+            } else {
+                let entries: Vec<_> = profile.time(STAGE_TRANSFORM, || {
+                    items.iter().map(|item| vocab::subjects::convert_item(item, options)).collect()
+                });
+                if let Some(count) = cli.preview {
+                    return print_preview(&entries, count, options).map(|()| None);
+                }
+                check_golden(cli, &entries, options)?;
+                profile.time(STAGE_WRITE, || vocab::affiliations::write_yaml_with_options(yaml_path, &entries, options))?;
+                write_lookup(cli, &entries)?;
+            }
+
+            summarize(items.len(), items.len(), Vec::new(), started, yaml_path, options)
+        }
+    };
+
+    if cli.profile {
+        eprint!("{}", profile);
+    }
+
+    if let Some(metrics_path) = &cli.metrics_file {
+        report.write_metrics(std::path::Path::new(metrics_path), vocab_type)?;
+    }
+
+    if let Some(dir) = &cli.fixtures_dir {
+        vocab::update_fixtures_index(Path::new(dir), vocab_type)?;
+    }
+
+    if let Some(hash) = input_hash {
+        std::fs::write(vocab::state_path(yaml_path), format!("{}\n", hash))?;
+    }
+
+    Ok(Some(report))
 }
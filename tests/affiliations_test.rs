@@ -1,4 +1,7 @@
-use invenio_vocb_converter::vocab::affiliations;
+// The baseline tests strip the BOM by hand; keep them as written.
+#![allow(clippy::manual_strip)]
+
+use invenio_vocb_converter::vocab::{affiliations, Verbosity, VocabType, WarningKind};
 use std::fs::{self, File};
 use std::io::Write;
 use tempfile::tempdir;
@@ -217,4 +220,1902 @@ fn test_edge_cases() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(special_yaml_data[0].acronym, Some("SCI".to_string()));
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_strip_html() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("html.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{
+            "id": "https://ror.org/00aaa1234",
+            "name": "Universit&eacute; <b>Test</b>",
+            "labels": [],
+            "acronyms": []
+        }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("html_output.yaml");
+    let options = affiliations::ConvertOptions {
+        strip_html: true,
+        ..Default::default()
+    };
+    affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    let yaml_content = fs::read_to_string(&yaml_path)?;
+    let yaml_data: Vec<affiliations::YamlEntry> =
+        serde_yaml::from_str(yaml_content.trim_start_matches('\u{FEFF}'))?;
+    assert_eq!(yaml_data[0].name, "Universite Test");
+    assert_eq!(yaml_data[0].title.get("en"), Some(&"Universite Test".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_default_id_schemes() -> Result<(), Box<dyn std::error::Error>> {
+    let options = affiliations::ConvertOptions::default();
+    assert_eq!(options.scheme_for(VocabType::Names), "orcid");
+    assert_eq!(options.scheme_for(VocabType::Affiliations), "ror");
+
+    let item: affiliations::AffiliationItem =
+        serde_json::from_str(r#"{"id": "https://ror.org/00aaa1234", "name": "Test"}"#)?;
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.identifiers[0].scheme, "ror");
+
+    // An explicit scheme overrides the per-type default.
+    let options = affiliations::ConvertOptions {
+        id_scheme: Some("grid".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(options.scheme_for(VocabType::Names), "grid");
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.identifiers[0].scheme, "grid");
+
+    Ok(())
+}
+
+#[test]
+fn test_on_empty_name_policies() -> Result<(), Box<dyn std::error::Error>> {
+    use affiliations::{ConvertOptions, EmptyNamePolicy};
+
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("empty_name.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Named Institute" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": null }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let keep = affiliations::convert_json_to_entries(&json_path, &ConvertOptions::default())?;
+    assert_eq!(keep.len(), 2);
+    assert_eq!(keep[1].name, "");
+
+    let options = ConvertOptions {
+        on_empty_name: EmptyNamePolicy::Skip,
+        ..Default::default()
+    };
+    let skip = affiliations::convert_json_to_entries(&json_path, &options)?;
+    assert_eq!(skip.len(), 1);
+    assert_eq!(skip[0].id, "00aaa1234");
+
+    let options = ConvertOptions {
+        on_empty_name: EmptyNamePolicy::Error,
+        ..Default::default()
+    };
+    let err = affiliations::convert_json_to_entries(&json_path, &options).unwrap_err();
+    assert!(err.to_string().contains("empty name"));
+
+    Ok(())
+}
+
+#[test]
+fn test_language_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("langs.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{
+            "id": "https://ror.org/00aaa1234",
+            "name": "First",
+            "labels": [
+                {{ "iso639": "fr", "label": "Premier" }},
+                {{ "iso639": "de", "label": "Erste" }}
+            ]
+        }},
+        {{
+            "id": "https://ror.org/00bbb5678",
+            "name": "Second",
+            "labels": [{{ "iso639": "fr", "label": "Deuxieme" }}]
+        }},
+        {{ "id": "https://ror.org/00ccc9012", "name": "Third" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let entries = affiliations::convert_json_to_entries(&json_path, &Default::default())?;
+    let stats = affiliations::language_stats(&entries);
+    assert_eq!(stats.get("en"), Some(&3));
+    assert_eq!(stats.get("fr"), Some(&2));
+    assert_eq!(stats.get("de"), Some(&1));
+
+    let stats_path = temp_dir.path().join("stats.tsv");
+    affiliations::write_language_stats(&stats_path, &entries)?;
+    let table = fs::read_to_string(&stats_path)?;
+    assert_eq!(table, "language\tentries\nde\t1\nen\t3\nfr\t2\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_tsv_input() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let tsv_path = temp_dir.path().join("affiliations.tsv");
+    let mut tsv_file = File::create(&tsv_path)?;
+    write!(
+        tsv_file,
+        "id\tname\tacronyms\tlabel_fr\n\
+         https://ror.org/00aaa1234\tTest University\tTU|TEST\tUniversité de Test\n\
+         https://ror.org/00bbb5678\t\"Another, Institute\"\t\t\n"
+    )?;
+    tsv_file.flush()?;
+
+    let options = affiliations::ConvertOptions {
+        input_format: affiliations::InputFormat::Csv,
+        delimiter: '\t',
+        ..Default::default()
+    };
+    let entries = affiliations::convert_json_to_entries(&tsv_path, &options)?;
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, "00aaa1234");
+    assert_eq!(entries[0].name, "Test University");
+    assert_eq!(entries[0].acronym, Some("TU".to_string()));
+    assert_eq!(entries[0].title.get("fr"), Some(&"Universite de Test".to_string()));
+    assert_eq!(entries[1].id, "00bbb5678");
+    assert_eq!(entries[1].name, "Another, Institute");
+    assert_eq!(entries[1].acronym, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_add_constant_title() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("collection.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "First Institute" }},
+        {{
+            "id": "https://ror.org/00bbb5678",
+            "name": "Second Institute",
+            "labels": [{{ "iso639": "fr", "label": "Deuxieme Institut" }}]
+        }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let options = affiliations::ConvertOptions {
+        extra_titles: vec![("collection".to_string(), "MyCollection".to_string())],
+        ..Default::default()
+    };
+    let entries = affiliations::convert_json_to_entries(&json_path, &options)?;
+
+    assert_eq!(entries.len(), 2);
+    for entry in &entries {
+        assert_eq!(entry.title.get("collection"), Some(&"MyCollection".to_string()));
+    }
+    assert_eq!(entries[1].title.get("fr"), Some(&"Deuxieme Institut".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_trailing_data_after_array() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("trailing.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[{{ "id": "https://ror.org/00aaa1234", "name": "Test" }}]garbage"#)?;
+    json_file.flush()?;
+
+    let err = affiliations::convert_json_to_entries(&json_path, &Default::default()).unwrap_err();
+    assert!(err.to_string().contains("Trailing data"));
+    assert!(err.to_string().contains("byte offset 55"));
+
+    let options = affiliations::ConvertOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let entries = affiliations::convert_json_to_entries(&json_path, &options)?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "00aaa1234");
+
+    Ok(())
+}
+
+#[test]
+fn test_fields_include_projection() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("projection.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test University", "acronyms": ["TU"] }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("projection.yaml");
+    let options = affiliations::ConvertOptions {
+        fields: affiliations::FieldProjection::Include(vec!["id".to_string(), "name".to_string()]),
+        ..Default::default()
+    };
+    affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    let yaml_content = fs::read_to_string(&yaml_path)?;
+    let yaml_data: Vec<serde_yaml::Mapping> =
+        serde_yaml::from_str(yaml_content.trim_start_matches('\u{FEFF}'))?;
+    assert_eq!(yaml_data.len(), 1);
+    let keys: Vec<&str> = yaml_data[0].iter().filter_map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, vec!["id", "name"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_respect_preferred_label() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem = serde_json::from_str(r#"{
+        "id": "https://ror.org/00aaa1234",
+        "name": "Test University",
+        "labels": [
+            { "iso639": "de", "label": "Test Universität" },
+            { "iso639": "fr", "label": "Université de Test", "preferred": true }
+        ]
+    }"#)?;
+
+    let entry = affiliations::convert_item(&item, &Default::default());
+    assert_eq!(entry.name, "Test University");
+
+    let options = affiliations::ConvertOptions {
+        respect_preferred: true,
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.name, "Universite de Test");
+    assert_eq!(entry.title.get("en"), Some(&"Universite de Test".to_string()));
+    assert_eq!(entry.title.get("fr"), Some(&"Universite de Test".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_strict_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("typo.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test", "acronymns": ["T"] }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let entries = affiliations::convert_json_to_entries(&json_path, &Default::default())?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].acronym, None);
+
+    let options = affiliations::ConvertOptions {
+        strict_fields: true,
+        ..Default::default()
+    };
+    let err = affiliations::convert_json_to_entries(&json_path, &options).unwrap_err();
+    assert!(err.to_string().contains("unknown field `acronymns`"));
+
+    Ok(())
+}
+
+#[test]
+fn test_changed_entries_against_baseline() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let baseline_json = temp_dir.path().join("baseline.json");
+    let mut json_file = File::create(&baseline_json)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "First Institute" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Second Institute" }}
+    ]"#)?;
+    json_file.flush()?;
+    let baseline_yaml = temp_dir.path().join("baseline.yaml");
+    affiliations::convert_json_to_yaml(&baseline_json, &baseline_yaml)?;
+
+    let input_json = temp_dir.path().join("input.json");
+    let mut json_file = File::create(&input_json)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "First Institute" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Second Institute (renamed)" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let entries = affiliations::convert_json_to_entries(&input_json, &Default::default())?;
+    let baseline = affiliations::read_yaml(&baseline_yaml)?;
+    let delta = affiliations::changed_entries(entries, &baseline);
+
+    assert_eq!(delta.len(), 1);
+    assert_eq!(delta[0].id, "00bbb5678");
+    assert_eq!(delta[0].name, "Second Institute (renamed)");
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_yaml_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("compact.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{
+            "id": "https://ror.org/00aaa1234",
+            "name": "Test: University",
+            "labels": [{{ "iso639": "fr", "label": "Université de Test" }}],
+            "acronyms": ["TU"]
+        }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Another Institute" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("compact.yaml");
+    let options = affiliations::ConvertOptions {
+        compact_yaml: true,
+        ..Default::default()
+    };
+    affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    let yaml_content = fs::read_to_string(&yaml_path)?;
+    let yaml_content = yaml_content.trim_start_matches('\u{FEFF}');
+    let lines: Vec<&str> = yaml_content.lines().filter(|line| *line != "---").collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.starts_with("- {") && line.ends_with('}')));
+
+    let compact: Vec<affiliations::YamlEntry> = serde_yaml::from_str(yaml_content)?;
+    let expected = affiliations::convert_json_to_entries(&json_path, &Default::default())?;
+    assert_eq!(compact, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_all_acronyms_case_insensitive_dedupe() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem = serde_json::from_str(r#"{
+        "id": "https://ror.org/042nb2s44",
+        "name": "Massachusetts Institute of Technology",
+        "acronyms": ["MIT", "mit", "M.I.T."]
+    }"#)?;
+
+    let entry = affiliations::convert_item(&item, &Default::default());
+    assert!(entry.acronyms.is_empty());
+
+    let options = affiliations::ConvertOptions {
+        acronym_strategy: affiliations::AcronymStrategy::All,
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.acronym, Some("MIT".to_string()));
+    assert_eq!(entry.acronyms, vec!["MIT".to_string(), "M.I.T.".to_string()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_input_shape_map() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let array_path = temp_dir.path().join("array.json");
+    let mut json_file = File::create(&array_path)?;
+    write!(json_file, r#"[
+        {{ "id": "00zzz9999", "name": "Zeta Institute" }},
+        {{ "id": "https://ror.org/00aaa1234", "name": "Alpha Institute", "acronyms": ["AI"] }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let map_path = temp_dir.path().join("map.json");
+    let mut json_file = File::create(&map_path)?;
+    write!(json_file, r#"{{
+        "00zzz9999": {{ "name": "Zeta Institute" }},
+        "00aaa1234": {{ "id": "https://ror.org/00aaa1234", "name": "Alpha Institute", "acronyms": ["AI"] }}
+    }}"#)?;
+    json_file.flush()?;
+
+    let options = affiliations::ConvertOptions {
+        input_shape: affiliations::InputShape::Map,
+        ..Default::default()
+    };
+    let from_map = affiliations::convert_json_to_entries(&map_path, &options)?;
+    let from_array = affiliations::convert_json_to_entries(&array_path, &Default::default())?;
+
+    assert_eq!(from_map.len(), 2);
+    assert_eq!(from_map, from_array);
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("verify.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test University", "acronyms": ["TU"] }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Another Institute" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("verify.yaml");
+    let options = affiliations::ConvertOptions {
+        verify_output: true,
+        ..Default::default()
+    };
+    affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    assert!(affiliations::verify_output(&yaml_path, 2, &options).is_ok());
+    let err = affiliations::verify_output(&yaml_path, 3, &options).unwrap_err();
+    assert!(err.to_string().contains("wrote 3 entries but read back 2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_deunicode_error_policies() -> Result<(), Box<dyn std::error::Error>> {
+    use affiliations::{ConvertOptions, DeunicodeErrors};
+
+    let item: affiliations::AffiliationItem = serde_json::from_str(
+        r#"{"id": "https://ror.org/00aaa1234", "name": "Test \ue000 Institute"}"#,
+    )?;
+    let items = vec![item];
+
+    let placeholder = affiliations::convert_items(&items, &ConvertOptions::default())?;
+    assert_eq!(placeholder[0].name, "Test [?] Institute");
+
+    let options = ConvertOptions {
+        deunicode_errors: DeunicodeErrors::Strip,
+        ..Default::default()
+    };
+    let strip = affiliations::convert_items(&items, &options)?;
+    assert_eq!(strip[0].name, "Test  Institute");
+
+    let options = ConvertOptions {
+        deunicode_errors: DeunicodeErrors::Error,
+        ..Default::default()
+    };
+    let err = affiliations::convert_items(&items, &options).unwrap_err();
+    assert!(err.to_string().contains("U+E000"));
+
+    Ok(())
+}
+
+#[test]
+fn test_records_path() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("nested.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"{{
+        "meta": {{ "count": 2 }},
+        "data": {{
+            "items": [
+                {{ "id": "https://ror.org/00aaa1234", "name": "First Institute" }},
+                {{ "id": "https://ror.org/00bbb5678", "name": "Second Institute" }}
+            ]
+        }}
+    }}"#)?;
+    json_file.flush()?;
+
+    let options = affiliations::ConvertOptions {
+        records_path: Some("data.items".to_string()),
+        ..Default::default()
+    };
+    let entries = affiliations::convert_json_to_entries(&json_path, &options)?;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].id, "00bbb5678");
+
+    let options = affiliations::ConvertOptions {
+        records_path: Some("data.results".to_string()),
+        ..Default::default()
+    };
+    let err = affiliations::convert_json_to_entries(&json_path, &options).unwrap_err();
+    assert!(err.to_string().contains("no element 'results'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_title_map_with_many_labels() -> Result<(), Box<dyn std::error::Error>> {
+    let labels: Vec<String> = (0..20)
+        .map(|i| format!(r#"{{ "iso639": "l{}", "label": "Label {}" }}"#, i, i))
+        .collect();
+    let json = format!(
+        r#"{{ "id": "https://ror.org/00aaa1234", "name": "Test University", "labels": [{}] }}"#,
+        labels.join(",")
+    );
+    let item: affiliations::AffiliationItem = serde_json::from_str(&json)?;
+
+    let options = affiliations::ConvertOptions {
+        extra_titles: vec![("collection".to_string(), "MyCollection".to_string())],
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+
+    let mut expected = std::collections::HashMap::new();
+    expected.insert("en".to_string(), "Test University".to_string());
+    for i in 0..20 {
+        expected.insert(format!("l{}", i), format!("Label {}", i));
+    }
+    expected.insert("collection".to_string(), "MyCollection".to_string());
+    assert_eq!(entry.title, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_alphabetize_acronyms() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem = serde_json::from_str(r#"{
+        "id": "https://ror.org/00aaa1234",
+        "name": "Test University",
+        "acronyms": ["TUX", "ATU", "MTU"]
+    }"#)?;
+
+    let options = affiliations::ConvertOptions {
+        acronym_strategy: affiliations::AcronymStrategy::All,
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.acronyms, vec!["TUX", "ATU", "MTU"]);
+
+    let options = affiliations::ConvertOptions {
+        acronym_strategy: affiliations::AcronymStrategy::All,
+        alphabetize_acronyms: true,
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.acronyms, vec!["ATU", "MTU", "TUX"]);
+    assert_eq!(entry.acronym, Some("TUX".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_vocab_version() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("version.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[{{ "id": "https://ror.org/00aaa1234", "name": "Test University" }}]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("version.yaml");
+    let options = affiliations::ConvertOptions {
+        vocab_version: Some("2024-06".to_string()),
+        version_in_props: true,
+        ..Default::default()
+    };
+    affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    let yaml_content = fs::read_to_string(&yaml_path)?;
+    let yaml_content = yaml_content.trim_start_matches('\u{FEFF}');
+    assert!(yaml_content.starts_with("# vocabulary version: 2024-06\n"));
+
+    let yaml_data: Vec<affiliations::YamlEntry> = serde_yaml::from_str(yaml_content)?;
+    assert_eq!(yaml_data[0].props.get("version"), Some(&"2024-06".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_identifier_base() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem =
+        serde_json::from_str(r#"{"id": "https://ror.org/00aaa1234", "name": "Test University"}"#)?;
+
+    let options = affiliations::ConvertOptions {
+        full_identifier: true,
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.id, "00aaa1234");
+    assert_eq!(entry.identifiers[0].identifier, "https://ror.org/00aaa1234");
+
+    let options = affiliations::ConvertOptions {
+        full_identifier: true,
+        identifier_base: Some("https://mirror.example.org/ror/".to_string()),
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.id, "00aaa1234");
+    assert_eq!(entry.identifiers[0].identifier, "https://mirror.example.org/ror/00aaa1234");
+
+    Ok(())
+}
+
+#[test]
+fn test_strip_legal_suffixes() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem =
+        serde_json::from_str(r#"{"id": "https://ror.org/00aaa1234", "name": "Example Research GmbH"}"#)?;
+
+    let entry = affiliations::convert_item(&item, &Default::default());
+    assert_eq!(entry.name, "Example Research GmbH");
+
+    let options = affiliations::ConvertOptions {
+        strip_legal_suffixes: true,
+        ..Default::default()
+    };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.name, "Example Research");
+    assert_eq!(entry.title.get("en"), Some(&"Example Research GmbH".to_string()));
+
+    assert_eq!(affiliations::strip_legal_suffix("Acme, Inc."), "Acme");
+    assert_eq!(affiliations::strip_legal_suffix("Imaging Ltd"), "Imaging");
+    assert_eq!(affiliations::strip_legal_suffix("Vitag"), "Vitag");
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_parquet_input() -> Result<(), Box<dyn std::error::Error>> {
+    use parquet::basic::Compression;
+    use parquet::data_type::{ByteArray, ByteArrayType};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use std::sync::Arc;
+
+    type Levels<'a> = Option<&'a [i16]>;
+
+    let dir = tempdir()?;
+    let input = dir.path().join("ror.parquet");
+    let schema = parquet::schema::parser::parse_message_type(
+        "message ror {
+            required binary id (UTF8);
+            required binary name (UTF8);
+            optional binary country_code (UTF8);
+            optional group acronyms (LIST) { repeated group list { optional binary element (UTF8); } }
+            optional binary label_fr (UTF8);
+        }",
+    )?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = SerializedFileWriter::new(File::create(&input)?, Arc::new(schema), Arc::new(properties))?;
+    let mut row_group = writer.next_row_group()?;
+    // (values, definition levels, repetition levels) per column, for two rows.
+    let columns: [(&[&str], Levels, Levels); 5] = [
+        (&["https://ror.org/00aaa1234", "https://ror.org/00bbb5678"], None, None),
+        (&["Test University", "Another Institute"], None, None),
+        (&["SE"], Some(&[1, 0]), None),
+        (&["TU", "TEST"], Some(&[3, 3, 0]), Some(&[0, 1, 0])),
+        (&["Université de Test"], Some(&[1, 0]), None),
+    ];
+    for (values, def_levels, rep_levels) in columns {
+        let mut column = row_group.next_column()?.ok_or("missing column")?;
+        let values: Vec<ByteArray> = values.iter().map(|&value| value.into()).collect();
+        column.typed::<ByteArrayType>().write_batch(&values, def_levels, rep_levels)?;
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+
+    let json_path = dir.path().join("ror.json");
+    fs::write(
+        &json_path,
+        r#"[
+            {"id": "https://ror.org/00aaa1234", "name": "Test University", "country_code": "SE", "acronyms": ["TU", "TEST"],
+             "labels": [{"iso639": "fr", "label": "Université de Test"}]},
+            {"id": "https://ror.org/00bbb5678", "name": "Another Institute"}
+        ]"#,
+    )?;
+
+    let options = affiliations::ConvertOptions { input_format: "parquet".parse()?, ..Default::default() };
+    let entries = affiliations::convert_json_to_entries(&input, &options)?;
+    assert_eq!(entries, affiliations::convert_json_to_entries(&json_path, &Default::default())?);
+    assert_eq!(entries[0].acronym, Some("TU".to_string()));
+
+    fs::write(&input, b"PAR1 truncated")?;
+    let err = affiliations::convert_json_to_entries(&input, &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ParseError>());
+    assert!(err.to_string().starts_with("Invalid Parquet input: "), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_sqlite_input() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let input = dir.path().join("authority.db");
+    let connection = rusqlite::Connection::open(&input)?;
+    connection.execute_batch(
+        "CREATE TABLE orgs (ror TEXT, label TEXT, country TEXT, acronyms TEXT, label_fr TEXT, founded INTEGER);
+         INSERT INTO orgs VALUES ('https://ror.org/00aaa1234', 'Test University', 'SE', 'TU|TEST', 'Université de Test', 1901);
+         INSERT INTO orgs VALUES ('https://ror.org/00bbb5678', 'Another Institute', NULL, NULL, NULL, NULL);",
+    )?;
+    drop(connection);
+
+    let json_path = dir.path().join("authority.json");
+    fs::write(
+        &json_path,
+        r#"[
+            {"id": "https://ror.org/00aaa1234", "name": "Test University", "country_code": "SE", "acronyms": ["TU", "TEST"],
+             "labels": [{"iso639": "fr", "label": "Université de Test"}]},
+            {"id": "https://ror.org/00bbb5678", "name": "Another Institute"}
+        ]"#,
+    )?;
+
+    let options = affiliations::ConvertOptions {
+        input_format: "sqlite".parse()?,
+        sqlite_query: Some(
+            "SELECT ror AS id, label AS name, country AS country_code, acronyms, label_fr FROM orgs ORDER BY ror".to_string(),
+        ),
+        ..Default::default()
+    };
+    let entries = affiliations::convert_json_to_entries(&input, &options)?;
+    assert_eq!(entries, affiliations::convert_json_to_entries(&json_path, &Default::default())?);
+    assert_eq!(entries[0].title.get("fr"), Some(&"Universite de Test".to_string()));
+
+    let options = affiliations::ConvertOptions {
+        input_format: "sqlite".parse()?,
+        sqlite_query: Some("SELECT id FROM missing_table".to_string()),
+        ..Default::default()
+    };
+    let err = affiliations::convert_json_to_entries(&input, &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ParseError>());
+    assert!(err.to_string().starts_with("Invalid SQLite input: "), "{}", err);
+
+    fs::write(&input, b"not a database")?;
+    let err = affiliations::convert_json_to_entries(&input, &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ParseError>());
+
+    Ok(())
+}
+
+#[test]
+fn test_report_collects_warnings() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let json_path = dir.path().join("input.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test", "labels": [{{ "iso639": "English", "label": "Test" }}] }},
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test again" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let options = affiliations::ConvertOptions {
+        on_empty_name: "skip".parse()?,
+        verbosity: Verbosity::Quiet,
+        ..Default::default()
+    };
+    let report = affiliations::convert_json_to_yaml_with_options(&json_path, &dir.path().join("out.yaml"), &options)?;
+
+    let kinds: Vec<_> = report.warnings.iter().map(|w| (w.kind, w.record)).collect();
+    assert_eq!(
+        kinds,
+        [(WarningKind::InvalidLanguage, 0), (WarningKind::DuplicateId, 1), (WarningKind::SkippedRecord, 2)]
+    );
+    assert!(report.warnings[0].message.contains("'English'"));
+    assert_eq!(report.emitted_total, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_language_tags_with_script_and_region_are_valid() -> Result<(), Box<dyn std::error::Error>> {
+    let items: Vec<affiliations::AffiliationItem> = serde_json::from_str(r#"[{
+        "id": "https://ror.org/00aaa1234",
+        "name": "Test",
+        "labels": [
+            { "iso639": "pt-BR", "label": "Teste" },
+            { "iso639": "zh-Hant", "label": "測試" },
+            { "iso639": "zh-Hant-TW", "label": "測試" },
+            { "iso639": "es-419", "label": "Prueba" },
+            { "iso639": "pt-", "label": "Teste" },
+            { "iso639": "en-Latn-US-x", "label": "Test" }
+        ]
+    }]"#)?;
+    let options = affiliations::ConvertOptions { verbosity: Verbosity::Quiet, ..Default::default() };
+
+    let (_, warnings) = affiliations::convert_items_with_warnings(&items, &options)?;
+    let invalid: Vec<_> = warnings.iter().filter(|w| w.kind == WarningKind::InvalidLanguage).map(|w| w.message.as_str()).collect();
+    assert_eq!(invalid.len(), 2, "{:?}", invalid);
+    assert!(!invalid.iter().any(|message| message.contains("'pt-BR'")));
+    assert!(invalid[0].contains("'pt-'"));
+    assert!(invalid[1].contains("'en-Latn-US-x'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_emit_country_name() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let json_path = dir.path().join("input.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test", "country_code": "DE" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Other", "country_code": "XX" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let options = affiliations::ConvertOptions {
+        emit_country_name: true,
+        verbosity: Verbosity::Quiet,
+        ..Default::default()
+    };
+    let yaml_path = dir.path().join("out.yaml");
+    let report = affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    let yaml = fs::read_to_string(&yaml_path)?;
+    assert!(yaml.contains("country: DE\n"));
+    assert!(yaml.contains("country_name: Germany\n"));
+    assert!(yaml.contains("country: XX\n"));
+    assert_eq!(yaml.matches("country_name:").count(), 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].kind, WarningKind::UnknownCountry);
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_json_to_writer() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test", "acronyms": ["T"] }]"#;
+    let mut buffer = Vec::new();
+
+    let report = affiliations::convert_json_to_writer(input.as_bytes(), &mut buffer, &Default::default())?;
+    assert_eq!(report.emitted_total, 1);
+
+    let yaml = String::from_utf8(buffer)?;
+    let yaml = yaml.strip_prefix('\u{FEFF}').unwrap_or(&yaml);
+    let entries: Vec<affiliations::YamlEntry> = serde_yaml::from_str(yaml)?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "00aaa1234");
+    assert_eq!(entries[0].acronym.as_deref(), Some("T"));
+
+    Ok(())
+}
+
+#[test]
+fn test_entry_id_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem =
+        serde_json::from_str(r#"{"id": "https://ror.org/00aaa1234", "name": "Test"}"#)?;
+    let options = affiliations::ConvertOptions {
+        entry_id_prefix: Some("ror:".to_string()),
+        ..Default::default()
+    };
+
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.id, "ror:00aaa1234");
+    assert_eq!(entry.identifiers[0].identifier, "00aaa1234");
+
+    Ok(())
+}
+
+#[test]
+fn test_watch_file_reruns_on_modification() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let json_path = dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#)?;
+
+    // Replace the file atomically so the watcher never sees it half-written.
+    let staged_path = dir.path().join("staged.json");
+    let writer_path = json_path.clone();
+    let modifier = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        fs::write(&staged_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }, { "id": "https://ror.org/00bbb5678", "name": "Other" }]"#)?;
+        fs::rename(&staged_path, writer_path)
+    });
+
+    let mut emitted = Vec::new();
+    invenio_vocb_converter::vocab::watch_file(&json_path, std::time::Duration::from_millis(10), || {
+        let entries = affiliations::convert_json_to_entries(&json_path, &Default::default()).unwrap();
+        emitted.push(entries.len());
+        if emitted.len() == 2 {
+            std::ops::ControlFlow::Break(())
+        } else {
+            std::ops::ControlFlow::Continue(())
+        }
+    })?;
+    modifier.join().unwrap()?;
+
+    assert_eq!(emitted, [1, 2]);
+
+    Ok(())
+}
+
+#[test]
+fn test_watch_file_debounces_bursts() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let json_path = dir.path().join("input.json");
+    let records = |count: usize| {
+        let records: Vec<String> =
+            (0..count).map(|n| format!(r#"{{ "id": "https://ror.org/00aaa{:04}", "name": "Test {}" }}"#, n, n)).collect();
+        format!("[{}]", records.join(","))
+    };
+    fs::write(&json_path, records(1))?;
+
+    // Several writes in quick succession, as an editor saving in steps would make.
+    let writer_path = json_path.clone();
+    let modifier = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        for count in 2..=5 {
+            fs::write(&writer_path, records(count))?;
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        Ok::<_, std::io::Error>(())
+    });
+
+    let mut emitted = Vec::new();
+    invenio_vocb_converter::vocab::watch_file(&json_path, std::time::Duration::from_millis(200), || {
+        emitted.push(affiliations::convert_json_to_entries(&json_path, &Default::default()).map(|entries| entries.len()).ok());
+        if emitted.len() == 2 {
+            std::ops::ControlFlow::Break(())
+        } else {
+            std::ops::ControlFlow::Continue(())
+        }
+    })?;
+    modifier.join().unwrap()?;
+
+    // The burst is converted once, after the last write.
+    assert_eq!(emitted, [Some(1), Some(5)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_max_acronym_length() -> Result<(), Box<dyn std::error::Error>> {
+    let long_acronym = "A".repeat(40);
+    let input = format!(
+        r#"[{{ "id": "https://ror.org/00aaa1234", "name": "Test", "acronyms": ["{}", "TU"] }}]"#,
+        long_acronym
+    );
+    let options = affiliations::ConvertOptions {
+        max_acronym_length: 10,
+        acronym_strategy: affiliations::AcronymStrategy::All,
+        verbosity: Verbosity::Quiet,
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    let report = affiliations::convert_json_to_writer(input.as_bytes(), &mut buffer, &options)?;
+    let yaml = String::from_utf8(buffer)?;
+
+    assert!(!yaml.contains(&long_acronym));
+    assert!(yaml.contains("acronym: TU\n"));
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].kind, WarningKind::LongAcronym);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_mode() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir()?;
+    let json_path = dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#)?;
+
+    let yaml_path = dir.path().join("out.yaml");
+    let options = affiliations::ConvertOptions {
+        output_mode: Some(0o640),
+        ..Default::default()
+    };
+    affiliations::convert_json_to_yaml_with_options(&json_path, &yaml_path, &options)?;
+
+    assert_eq!(fs::metadata(&yaml_path)?.permissions().mode() & 0o777, 0o640);
+
+    Ok(())
+}
+
+#[test]
+fn test_identifier_key() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#;
+    let options = affiliations::ConvertOptions {
+        identifier_key: "identifier".to_string(),
+        ..Default::default()
+    };
+
+    let mut buffer = Vec::new();
+    affiliations::convert_json_to_writer(input.as_bytes(), &mut buffer, &options)?;
+    let yaml = String::from_utf8(buffer)?;
+
+    assert!(yaml.contains("\n  identifier:\n    - identifier: 00aaa1234\n"));
+    assert!(!yaml.contains("identifiers:"));
+    assert!(yaml.contains("scheme: ror"));
+
+    Ok(())
+}
+
+#[test]
+fn test_include_labels_from() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let labels_path = dir.path().join("translations.json");
+    fs::write(&labels_path, r#"{ "https://ror.org/00aaa1234": { "fr": "Institut local" } }"#)?;
+
+    let json_path = dir.path().join("affiliations.json");
+    fs::write(&json_path, r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test", "labels": [{ "iso639": "fr", "label": "Institut source" }] },
+        { "id": "https://ror.org/00bbb5678", "name": "Other" }
+    ]"#)?;
+    let options = affiliations::ConvertOptions {
+        included_labels: affiliations::read_included_labels(&labels_path)?,
+        ..Default::default()
+    };
+
+    let entries = affiliations::convert_json_to_entries(&json_path, &options)?;
+    assert_eq!(entries[0].title.get("fr").map(String::as_str), Some("Institut local"));
+    assert_eq!(entries[0].title.get("en").map(String::as_str), Some("Test"));
+    assert!(!entries[1].title.contains_key("fr"));
+
+    Ok(())
+}
+
+#[test]
+fn test_input_schema() -> Result<(), Box<dyn std::error::Error>> {
+    let schema = invenio_vocb_converter::vocab::input_schema(VocabType::Affiliations).expect("affiliations schema");
+    // Record types are emitted once under `$defs` and referenced from where they are used.
+    let resolve = |node: &serde_json::Value| {
+        let name = node["$ref"].as_str().and_then(|r| r.strip_prefix("#/$defs/")).expect("a local $ref");
+        schema["$defs"][name].clone()
+    };
+    let record = resolve(&schema["items"]);
+    let properties = &record["properties"];
+
+    assert_eq!(schema["type"], "array");
+    assert_eq!(schema["title"], "affiliations input");
+    assert!(properties.get("labels").is_some());
+    assert!(properties.get("acronyms").is_some());
+    assert_eq!(record["required"], serde_json::json!(["name"]));
+    assert_eq!(resolve(&properties["labels"]["items"])["properties"]["iso639"]["type"][0], "string");
+    assert!(invenio_vocb_converter::vocab::input_schema(VocabType::Funding).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_acronym_strategies() -> Result<(), Box<dyn std::error::Error>> {
+    use affiliations::AcronymStrategy;
+
+    let item: affiliations::AffiliationItem =
+        serde_json::from_str(r#"{"id": "https://ror.org/00aaa1234", "name": "Test", "acronyms": ["TU", "TUT", "TSTU"]}"#)?;
+    let convert = |strategy: AcronymStrategy| {
+        let options = affiliations::ConvertOptions { acronym_strategy: strategy, ..Default::default() };
+        affiliations::convert_item(&item, &options)
+    };
+
+    let first = convert(AcronymStrategy::First);
+    assert_eq!(first.acronym.as_deref(), Some("TU"));
+    assert!(first.acronyms.is_empty());
+    assert!(first.props.is_empty());
+
+    let all = convert(AcronymStrategy::All);
+    assert_eq!(all.acronyms, ["TU", "TUT", "TSTU"]);
+    assert!(all.props.is_empty());
+
+    let variants = convert(AcronymStrategy::TitleVariants);
+    assert_eq!(variants.acronym.as_deref(), Some("TU"));
+    assert!(variants.acronyms.is_empty());
+    assert_eq!(variants.props.get("title_variants").map(String::as_str), Some("TU; TUT; TSTU"));
+
+    let both = convert(AcronymStrategy::AllAndVariants);
+    assert_eq!(both.acronyms, ["TU", "TUT", "TSTU"]);
+    assert_eq!(both.props.get("title_variants").map(String::as_str), Some("TU; TUT; TSTU"));
+
+    Ok(())
+}
+
+#[test]
+fn test_whitespace_duplicate_labels_merge() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem = serde_json::from_str(
+        r#"{
+            "id": "https://ror.org/00aaa1234",
+            "name": "Other",
+            "labels": [
+                { "iso639": "en", "label": "Test " },
+                { "iso639": "en", "label": "Test" },
+                { "iso639": "fr", "label": "Essai  de  test" },
+                { "iso639": "fr", "label": " Essai de test" }
+            ]
+        }"#,
+    )?;
+
+    let entry = affiliations::convert_item(&item, &Default::default());
+    assert_eq!(entry.title.get("en").map(String::as_str), Some("Test"));
+    assert_eq!(entry.title.get("fr").map(String::as_str), Some("Essai de test"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lang_allowlist() -> Result<(), Box<dyn std::error::Error>> {
+    let item: affiliations::AffiliationItem = serde_json::from_str(
+        r#"{
+            "id": "https://ror.org/00aaa1234",
+            "name": "Test",
+            "labels": [
+                { "iso639": "fr", "label": "Essai" },
+                { "iso639": "zh", "label": "测试" }
+            ]
+        }"#,
+    )?;
+    let options = affiliations::ConvertOptions {
+        lang_allowlist: Some(vec!["en".to_string(), "fr".to_string()]),
+        ..Default::default()
+    };
+
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.title.get("en").map(String::as_str), Some("Test"));
+    assert_eq!(entry.title.get("fr").map(String::as_str), Some("Essai"));
+    assert!(!entry.title.contains_key("zh"));
+
+    // The primary title survives even when `en` is not listed.
+    let options = affiliations::ConvertOptions { lang_allowlist: Some(vec!["fr".to_string()]), ..Default::default() };
+    let entry = affiliations::convert_item(&item, &options);
+    assert_eq!(entry.title.len(), 2);
+    assert!(entry.title.contains_key("en"));
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_stream_catches_late_error() -> Result<(), Box<dyn std::error::Error>> {
+    let records = 50_000;
+    let mut input = String::from("[");
+    for index in 0..records {
+        if index > 0 {
+            input.push(',');
+        }
+        if index == records - 3 {
+            input.push_str(r#"{"id": "https://ror.org/bad", "name": 42}"#);
+        } else {
+            input.push_str(&format!(r#"{{"id": "https://ror.org/{:09}", "name": "Institute {}"}}"#, index, index));
+        }
+    }
+    input.push(']');
+
+    let errors = affiliations::validate_stream(input.as_bytes(), &Default::default(), 10);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].record, records - 3);
+
+    // A truncated document is reported after the records read so far.
+    let errors = affiliations::validate_stream(&input.as_bytes()[..input.len() - 1], &Default::default(), 10);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[1].record, records);
+
+    // Validation stops once the configured number of errors is reached.
+    let bad = format!("[{}]", vec![r#"{"name": 1}"#; 100].join(","));
+    assert_eq!(affiliations::validate_stream(bad.as_bytes(), &Default::default(), 3).len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_fold_diacritics_only() -> Result<(), Box<dyn std::error::Error>> {
+    let options = affiliations::ConvertOptions {
+        fold_diacritics_only: true,
+        ..Default::default()
+    };
+
+    assert_eq!(options.clean("Genève"), "Geneve");
+    assert_eq!(options.clean("Université de Montréal"), "Universite de Montreal");
+    assert_eq!(options.clean("Москва"), "Москва");
+    assert_eq!(options.clean("Straße Ørsted"), "Straße Ørsted");
+    assert_eq!(options.clean("Cafe\u{301}"), "Cafe");
+
+    Ok(())
+}
+
+#[test]
+fn test_fold_diacritics_precomposed_and_decomposed() -> Result<(), Box<dyn std::error::Error>> {
+    use affiliations::fold_diacritics;
+
+    // Vietnamese horned letters: Ơ (U+01A0) and Ư (U+01AF), precomposed and as base + U+031B.
+    assert_eq!(fold_diacritics("\u{01A0} \u{01AF}"), "O U");
+    assert_eq!(fold_diacritics("O\u{031B} U\u{031B}"), "O U");
+    assert_eq!(fold_diacritics("Trường Đại học Bách khoa"), "Truong Đai hoc Bach khoa");
+    assert_eq!(fold_diacritics("Tru\u{031B}\u{0300}o\u{031B}ng"), "Truong");
+    // Marks on other scripts are kept, recomposed.
+    assert_eq!(fold_diacritics("Майкоп"), "Майкоп");
+    assert_eq!(fold_diacritics("Ἀθῆναι"), "Ἀθῆναι");
+
+    Ok(())
+}
+
+#[test]
+fn test_homepage_url() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test", "established": 1901, "links": ["https://www.test.example/"] },
+        {
+            "id": "https://ror.org/00bbb5678",
+            "name": "Other",
+            "links": [{ "type": "wikipedia", "value": "https://en.wikipedia.org/wiki/Other" }, { "type": "website", "value": "not a url" }],
+            "wikipedia_url": "https://en.wikipedia.org/wiki/Other"
+        }
+    ]"#;
+    let options = affiliations::ConvertOptions { verbosity: Verbosity::Quiet, ..Default::default() };
+
+    let items = affiliations::read_items_from(input.as_bytes(), &options)?;
+    let (entries, warnings) = affiliations::convert_items_with_warnings(&items, &options)?;
+
+    assert_eq!(entries[0].url.as_deref(), Some("https://www.test.example/"));
+    assert_eq!(entries[1].url.as_deref(), Some("https://en.wikipedia.org/wiki/Other"));
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::InvalidUrl);
+
+    Ok(())
+}
+
+#[test]
+fn test_generated_ids_are_stable() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "name": "Test University" },
+        { "id": "https://ror.org/test-university-2", "name": "Real" },
+        { "name": "Test  University" },
+        { "name": "Université Test" },
+        { "name": "Test University" }
+    ]"#;
+    let generate = |seed: Option<u64>| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let options = affiliations::ConvertOptions { generate_ids: true, seed, ..Default::default() };
+        let items = affiliations::read_items_from(input.as_bytes(), &options)?;
+        Ok(affiliations::convert_items(&items, &options)?.into_iter().map(|entry| entry.id).collect())
+    };
+
+    let first = generate(None)?;
+    assert_eq!(first, ["test-university", "test-university-2", "test-university-3", "universite-test", "test-university-4"]);
+    assert_eq!(generate(None)?, first);
+
+    let seeded = generate(Some(42))?;
+    assert_eq!(generate(Some(42))?, seeded);
+    assert_eq!(seeded[0], "test-university");
+    assert_ne!(seeded[2], first[2]);
+    assert_ne!(seeded[2], seeded[4]);
+
+    Ok(())
+}
+
+#[test]
+fn test_emit_lookup() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University" },
+        { "id": "https://ror.org/00bbb5678", "name": "Other Institute" },
+        { "id": "", "name": "No Id" }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+
+    let lookup_path = dir.path().join("lookup.json");
+    affiliations::write_lookup(&lookup_path, &entries)?;
+
+    let content = fs::read_to_string(&lookup_path)?;
+    assert_eq!(content, "{\"00aaa1234\":\"Test University\",\"00bbb5678\":\"Other Institute\"}\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_null_labels_and_acronyms() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test", "labels": null, "acronyms": null }]"#;
+
+    for strict_fields in [false, true] {
+        let options = affiliations::ConvertOptions { strict_fields, ..Default::default() };
+        let items = affiliations::read_items_from(input.as_bytes(), &options)?;
+        let entries = affiliations::convert_items(&items, &options)?;
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title.len(), 1);
+        assert_eq!(entries[0].title.get("en").map(String::as_str), Some("Test"));
+        assert_eq!(entries[0].acronym, None);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_strict_utf8_reports_offset() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = br#"[{ "id": "https://ror.org/00aaa1234", "name": "Caf"#.to_vec();
+    let offset = input.len();
+    input.extend_from_slice(b"\xFF\" }]");
+    let options = affiliations::ConvertOptions { strict_utf8: true, ..Default::default() };
+
+    let err = affiliations::read_items_from(input.as_slice(), &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ParseError>());
+    assert_eq!(
+        err.to_string(),
+        format!("Invalid UTF-8 at byte offset {} (line 1): unexpected byte sequence FF", offset)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_use_anchors_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test", "acronyms": ["T"] },
+        { "id": "https://ror.org/00bbb5678", "name": "Other" },
+        { "id": "https://ror.org/00ccc9012", "name": "Third" }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+
+    let options = affiliations::ConvertOptions { use_anchors: true, ..Default::default() };
+    let yaml = affiliations::entries_to_yaml(&entries, &options)?;
+    assert_eq!(yaml.matches("scheme: &scheme_ror ror").count(), 1);
+    assert_eq!(yaml.matches("scheme: *scheme_ror").count(), 2);
+
+    let parsed: Vec<affiliations::YamlEntry> = serde_yaml::from_str(&yaml)?;
+    assert_eq!(parsed, entries);
+
+    Ok(())
+}
+
+#[test]
+fn test_name_from_id_fallback() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/05dxps055", "name": null }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+    assert_eq!(entries[0].name, "");
+
+    let options = affiliations::ConvertOptions { name_from_id_fallback: true, ..Default::default() };
+    let entries = affiliations::convert_items(&items, &options)?;
+    assert_eq!(entries[0].name, "05dxps055");
+    assert_eq!(entries[0].title.get("en").map(String::as_str), Some("05dxps055"));
+
+    Ok(())
+}
+
+#[test]
+fn test_split_acronyms() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/042nb2s44", "name": "Massachusetts Institute of Technology", "acronyms": ["MIT, M.I.T."] }]"#;
+    let options = affiliations::ConvertOptions {
+        split_acronyms: true,
+        acronym_strategy: affiliations::AcronymStrategy::All,
+        ..Default::default()
+    };
+    let items = affiliations::read_items_from(input.as_bytes(), &options)?;
+    assert_eq!(items[0].acronyms, vec!["MIT", "M.I.T."]);
+
+    let entries = affiliations::convert_items(&items, &options)?;
+    assert_eq!(entries[0].acronym.as_deref(), Some("MIT"));
+    assert_eq!(entries[0].acronyms, vec!["MIT", "M.I.T."]);
+
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    assert_eq!(items[0].acronyms, vec!["MIT, M.I.T."]);
+
+    Ok(())
+}
+
+#[test]
+fn test_golden_shape_drift() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let golden_path = temp_dir.path().join("golden.yaml");
+    fs::write(&golden_path, "- id: 00aaa1234\n  name: Test University\n  title:\n    en: Test University\n")?;
+    let golden = affiliations::read_golden(&golden_path)?;
+
+    let input = r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test University" }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+    affiliations::check_golden(&golden, &entries, &Default::default())?;
+
+    let options = affiliations::ConvertOptions {
+        fields: affiliations::FieldProjection::Exclude(vec!["title".to_string()]),
+        ..Default::default()
+    };
+    let err = affiliations::check_golden(&golden, &entries, &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ValidationError>());
+    assert_eq!(err.to_string(), "Entry 0 (id '00aaa1234') does not match the golden shape: missing title");
+
+    Ok(())
+}
+
+#[test]
+fn test_scheme_case_is_normalized() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test University" }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    let options = affiliations::ConvertOptions { id_scheme: Some("ROR".to_string()), ..Default::default() };
+    let entries = affiliations::convert_items(&items, &options)?;
+    assert_eq!(entries[0].identifiers[0].scheme, "ror");
+
+    let options = affiliations::ConvertOptions { preserve_scheme_case: true, ..options };
+    let entries = affiliations::convert_items(&items, &options)?;
+    assert_eq!(entries[0].identifiers[0].scheme, "ROR");
+
+    Ok(())
+}
+
+#[test]
+fn test_gzip_csv_matches_plain_csv() -> Result<(), Box<dyn std::error::Error>> {
+    let plain = include_bytes!("fixtures/affiliations.csv");
+    let compressed = include_bytes!("fixtures/affiliations.csv.gz");
+    let options = affiliations::ConvertOptions {
+        input_format: affiliations::InputFormat::Csv,
+        ..Default::default()
+    };
+
+    let expected = affiliations::convert_items(&affiliations::read_items_from(&plain[..], &options)?, &options)?;
+    let entries = affiliations::convert_items(&affiliations::read_items_from(&compressed[..], &options)?, &options)?;
+    assert_eq!(entries.len(), 10);
+    assert_eq!(entries, expected);
+
+    // A corrupted stream is a parse error rather than garbage records.
+    let mut corrupted = compressed.to_vec();
+    let last = corrupted.len() - 5;
+    corrupted[last] ^= 0xff;
+    let err = affiliations::read_items_from(corrupted.as_slice(), &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ParseError>());
+
+    Ok(())
+}
+
+#[test]
+fn test_dedupe_titles() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{
+        "id": "https://ror.org/01ggx4157",
+        "name": "CERN",
+        "labels": [
+            { "iso639": "fr", "label": "CERN" },
+            { "iso639": "de", "label": "Europaische Organisation fur Kernforschung" }
+        ]
+    }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+    assert_eq!(entries[0].title.get("fr").map(String::as_str), Some("CERN"));
+
+    let options = affiliations::ConvertOptions { dedupe_titles: true, ..Default::default() };
+    let entries = affiliations::convert_items(&items, &options)?;
+    assert_eq!(entries[0].title.get("en").map(String::as_str), Some("CERN"));
+    assert!(!entries[0].title.contains_key("fr"));
+    assert!(entries[0].title.contains_key("de"));
+
+    Ok(())
+}
+
+#[test]
+fn test_external_ids_become_identifiers() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        {
+            "id": "https://ror.org/01ggx4157",
+            "name": "CERN",
+            "external_ids": {
+                "GRID": { "preferred": "grid.9132.9", "all": "grid.9132.9" },
+                "Wikidata": { "preferred": null, "all": ["Q42944"] }
+            }
+        },
+        {
+            "id": "https://ror.org/05dxps055",
+            "name": "Caltech",
+            "external_ids": [
+                { "type": "wikidata", "preferred": "Q161562", "all": ["Q161562"] },
+                { "type": "grid", "all": ["grid.20861.3d"] }
+            ]
+        },
+        {
+            "id": "https://ror.org/042nb2s44",
+            "name": "MIT",
+            "external_ids": { "Wikidata": ["Q49108"] }
+        }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+
+    let identifiers = |index: usize| -> Vec<(String, String)> {
+        entries[index].identifiers.iter().map(|i| (i.scheme.clone(), i.identifier.clone())).collect()
+    };
+    let pair = |scheme: &str, identifier: &str| (scheme.to_string(), identifier.to_string());
+    assert_eq!(
+        identifiers(0),
+        vec![pair("ror", "01ggx4157"), pair("grid", "grid.9132.9"), pair("wikidata", "Q42944")]
+    );
+    assert_eq!(
+        identifiers(1),
+        vec![pair("ror", "05dxps055"), pair("wikidata", "Q161562"), pair("grid", "grid.20861.3d")]
+    );
+    assert_eq!(identifiers(2), vec![pair("ror", "042nb2s44"), pair("wikidata", "Q49108")]);
+
+    Ok(())
+}
+
+#[test]
+fn test_crlf_output_newline() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test University", "acronyms": ["TU"] }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+    let options = affiliations::ConvertOptions {
+        output_newline: "crlf".parse()?,
+        vocab_version: Some("v1.0".to_string()),
+        ..Default::default()
+    };
+
+    let mut output = Vec::new();
+    affiliations::write_entries(&mut output, &entries, &options)?;
+    let text = String::from_utf8(output)?;
+
+    assert!(text.starts_with("\u{FEFF}# vocabulary version: v1.0\r\n"));
+    assert!(text.contains("name: Test University\r\n"));
+    assert_eq!(text.matches('\n').count(), text.matches("\r\n").count());
+    let parsed: Vec<affiliations::YamlEntry> = serde_yaml::from_str(text.trim_start_matches('\u{FEFF}'))?;
+    assert_eq!(parsed, entries);
+
+    Ok(())
+}
+
+#[test]
+fn test_entry_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University", "types": ["Education"] },
+        { "id": "https://ror.org/00bbb5678", "name": "Test Hospital", "types": ["Healthcare", "Facility"] }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    let entries = affiliations::convert_items(&items, &Default::default())?;
+    assert!(entries.iter().all(|entry| entry.tags.is_empty()));
+
+    let options = affiliations::ConvertOptions {
+        tags: vec!["featured".to_string()],
+        tags_from_types: true,
+        ..Default::default()
+    };
+    let entries = affiliations::convert_items(&items, &options)?;
+    assert_eq!(entries[0].tags, vec!["featured", "education"]);
+    assert_eq!(entries[1].tags, vec!["featured", "healthcare", "facility"]);
+    assert!(affiliations::entries_to_yaml(&entries, &options)?.contains("tags:\n    - featured\n    - education\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_checkpoint_resumes_interrupted_write() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let input = r#"[
+        { "id": "https://ror.org/00aaa0001", "name": "First University" },
+        { "id": "https://ror.org/00aaa0002", "name": "Second University" },
+        { "id": "https://ror.org/00aaa0003", "name": "Third University" },
+        { "id": "https://ror.org/00aaa0004", "name": "Fourth University" }
+    ]"#;
+    let options = affiliations::ConvertOptions::default();
+    let items = affiliations::read_items_from(input.as_bytes(), &options)?;
+    let (entries, records, _) = affiliations::convert_items_with_records(&items, &options)?;
+
+    let expected_path = temp_dir.path().join("expected.yaml");
+    affiliations::write_yaml_with_options(&expected_path, &entries, &options)?;
+
+    // The interrupted run got as far as record 2 of 4.
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let checkpoint_path = temp_dir.path().join("output.checkpoint");
+    let resumed = affiliations::write_yaml_with_checkpoint(&yaml_path, &entries[..2], &records[..2], 2, &checkpoint_path, &options)?;
+    assert_eq!(resumed, 0);
+    assert_eq!(affiliations::read_checkpoint(&checkpoint_path)?, 2);
+    assert_eq!(affiliations::read_yaml(&yaml_path)?.len(), 2);
+
+    let resumed = affiliations::write_yaml_with_checkpoint(&yaml_path, &entries, &records, 4, &checkpoint_path, &options)?;
+    assert_eq!(resumed, 2);
+    assert_eq!(affiliations::read_checkpoint(&checkpoint_path)?, 4);
+    assert_eq!(fs::read_to_string(&yaml_path)?, fs::read_to_string(&expected_path)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_identifiers_are_detected() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University",
+          "external_ids": { "Wikidata": ["Q1"] } },
+        { "id": "https://ror.org/00bbb5678", "name": "Other University" },
+        { "id": "https://ror.org/00ccc9012", "name": "Test University Hospital",
+          "external_ids": { "Wikidata": ["Q1"] } }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    let options = affiliations::ConvertOptions { verbosity: Verbosity::Quiet, ..Default::default() };
+    let (entries, warnings) = affiliations::convert_items_with_warnings(&items, &options)?;
+    assert_eq!(affiliations::duplicate_identifiers(&entries), vec![(2, 0, "Q1")]);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::DuplicateIdentifier);
+    assert_eq!(warnings[0].record, 2);
+
+    let options = affiliations::ConvertOptions { strict: true, ..options };
+    let err = affiliations::convert_items(&items, &options).unwrap_err();
+    assert!(err.is::<invenio_vocb_converter::vocab::ValidationError>());
+    assert_eq!(err.to_string(), "Duplicate identifiers: 'Q1' (records 0 and 2)");
+
+    Ok(())
+}
+
+#[test]
+fn test_input_format_is_detected() -> Result<(), Box<dyn std::error::Error>> {
+    let inputs = [
+        (
+            affiliations::InputFormat::Json,
+            "[\n  { \"id\": \"https://ror.org/00aaa1234\", \"name\": \"Test University\", \"acronyms\": [\"TU\"] }\n]\n",
+        ),
+        (affiliations::InputFormat::Csv, "id,name,acronyms\nhttps://ror.org/00aaa1234,Test University,TU\n"),
+        (affiliations::InputFormat::Csv, "id\tname\tacronyms\nhttps://ror.org/00aaa1234\tTest University\tTU\n"),
+    ];
+
+    let auto = affiliations::ConvertOptions { input_format: affiliations::InputFormat::Auto, ..Default::default() };
+    for (format, input) in inputs {
+        assert_eq!(affiliations::detect_input_format(input.as_bytes()), format, "{}", input);
+        let items = affiliations::read_items_from(input.as_bytes(), &auto)?;
+        let entries = affiliations::convert_items(&items, &Default::default())?;
+        assert_eq!(entries.len(), 1, "{}", input);
+        assert_eq!(entries[0].id, "00aaa1234");
+        assert_eq!(entries[0].name, "Test University");
+        assert_eq!(entries[0].acronym.as_deref(), Some("TU"));
+    }
+
+    let lines = "{ \"id\": \"a\", \"name\": \"A\" }\n{ \"id\": \"b\", \"name\": \"B\" }\n{ \"id\": \"c\" }\n";
+    assert_eq!(affiliations::detect_input_format(lines.as_bytes()), affiliations::InputFormat::JsonLines);
+    let err = affiliations::read_items_from(lines.as_bytes(), &auto).unwrap_err();
+    assert!(err.to_string().starts_with("Invalid JSON record on line 3: missing field `name`"), "{}", err);
+
+    // A lone object on one line is a JSON document, not a one-record JSON Lines file.
+    let object = "{ \"00aaa1234\": { \"id\": \"https://ror.org/00aaa1234\", \"name\": \"Test University\" } }\n";
+    assert_eq!(affiliations::detect_input_format(object.as_bytes()), affiliations::InputFormat::Json);
+    let err = affiliations::read_items_from(object.as_bytes(), &auto).unwrap_err();
+    assert!(err.to_string().starts_with("invalid type: map, expected a sequence"), "{}", err);
+
+    assert_eq!(affiliations::detect_input_format(b"PAR1\x15\x04"), affiliations::InputFormat::Parquet);
+    assert_eq!(affiliations::detect_input_format(b"SQLite format 3\0\x10\x00"), affiliations::InputFormat::Sqlite);
+    assert_eq!(affiliations::InputFormat::default(), affiliations::InputFormat::Json);
+
+    Ok(())
+}
+
+#[test]
+fn test_compare_with_registry_warns_on_unknown_ids() -> Result<(), Box<dyn std::error::Error>> {
+    use invenio_vocb_converter::vocab::registry::Registry;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    // A mock HTTPS registry with a self-signed certificate for localhost. It knows 00aaa1234,
+    // which it moves with a redirect and serves chunked, and answers one request per connection.
+    let certificate = include_bytes!("fixtures/localhost.crt.der");
+    let key = include_bytes!("fixtures/localhost.key.der");
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![certificate.to_vec().into()],
+            rustls::pki_types::PrivateKeyDer::Pkcs8(key.to_vec().into()),
+        )?;
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let server = std::thread::spawn(move || {
+        let mut requested = Vec::new();
+        for stream in listener.incoming().take(3) {
+            let connection = rustls::ServerConnection::new(config.clone()).unwrap();
+            let mut tls = rustls::StreamOwned::new(connection, stream.unwrap());
+            let path = {
+                let mut lines = BufReader::new(&mut tls).lines().map(Result::unwrap);
+                let request_line = lines.next().unwrap();
+                lines.take_while(|line| !line.is_empty()).for_each(drop);
+                request_line.split_whitespace().nth(1).unwrap().to_string()
+            };
+            let response = match path.as_str() {
+                "/organizations/00aaa1234" => "HTTP/1.1 301 Moved Permanently\r\nLocation: /v2/organizations/00aaa1234\r\n\
+                                               Content-Length: 0\r\nConnection: close\r\n\r\n",
+                "/v2/organizations/00aaa1234" => "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+                                                  2\r\n{}\r\n0\r\n\r\n",
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            };
+            tls.write_all(response.as_bytes()).unwrap();
+            tls.conn.send_close_notify();
+            tls.flush().unwrap();
+            requested.push(path);
+        }
+        requested
+    });
+
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University" },
+        { "id": "https://ror.org/00zzz9999", "name": "Withdrawn Institute" }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let options = affiliations::ConvertOptions { verbosity: Verbosity::Quiet, ..Default::default() };
+    let (entries, records, _) = affiliations::convert_items_with_records(&items, &options)?;
+
+    // The default configuration with only the URL and trusted root swapped for the mock's.
+    let registry = Registry {
+        url: format!("https://localhost:{}/organizations/", port),
+        requests_per_second: 0,
+        root_certificates: vec![certificate.to_vec()],
+        ..Default::default()
+    };
+    let warnings = affiliations::compare_with_registry(&entries, &records, &items, &registry, &options)?;
+    assert_eq!(
+        server.join().unwrap(),
+        vec!["/organizations/00aaa1234", "/v2/organizations/00aaa1234", "/organizations/00zzz9999"]
+    );
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::UnknownRegistryId);
+    assert_eq!(warnings[0].record, 1);
+    assert_eq!(warnings[0].id, "https://ror.org/00zzz9999");
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_vocab_writer() -> Result<(), Box<dyn std::error::Error>> {
+    use affiliations::{VocabWriter, YamlEntry};
+
+    // Collects entry ids in memory instead of serializing them.
+    #[derive(Default)]
+    struct IdCollector<'a> {
+        ids: Vec<String>,
+        finished: Option<&'a mut Vec<String>>,
+    }
+
+    impl VocabWriter for IdCollector<'_> {
+        fn write(&mut self, entry: &YamlEntry) -> Result<(), Box<dyn std::error::Error>> {
+            self.ids.push(entry.id.clone());
+            Ok(())
+        }
+
+        fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+            if let Some(finished) = self.finished {
+                *finished = self.ids;
+            }
+            Ok(())
+        }
+    }
+
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University" },
+        { "id": "https://ror.org/00bbb5678", "name": "Other University" }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let options = affiliations::ConvertOptions::default();
+    let entries = affiliations::convert_items(&items, &options)?;
+
+    let mut collected = Vec::new();
+    affiliations::write_with(IdCollector { finished: Some(&mut collected), ..Default::default() }, &entries)?;
+    assert_eq!(collected, vec!["00aaa1234", "00bbb5678"]);
+
+    // The built-in YAML writer streams the same document `entries_to_yaml` renders.
+    let mut output = Vec::new();
+    affiliations::write_with(affiliations::YamlWriter::new(&mut output, &options), &entries)?;
+    assert_eq!(String::from_utf8(output)?, format!("\u{feff}{}", affiliations::entries_to_yaml(&entries, &options)?));
+
+    Ok(())
+}
+
+#[test]
+fn test_collapse_dialects() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{
+        "id": "https://ror.org/00aaa1234",
+        "name": "Test University",
+        "labels": [
+            { "iso639": "pt-BR", "label": "Universidade de Teste" },
+            { "iso639": "pt-PT", "label": "Universidade de Testes" }
+        ]
+    }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let quiet = affiliations::ConvertOptions { verbosity: Verbosity::Quiet, ..Default::default() };
+
+    let (entries, warnings) = affiliations::convert_items_with_warnings(&items, &quiet)?;
+    assert!(entries[0].title.contains_key("pt-BR") && entries[0].title.contains_key("pt-PT"));
+    assert!(warnings.is_empty());
+
+    let options = affiliations::ConvertOptions { collapse_dialects: true, ..quiet };
+    let (entries, warnings) = affiliations::convert_items_with_warnings(&items, &options)?;
+    let languages: Vec<&str> = entries[0].title.keys().map(String::as_str).filter(|lang| *lang != "en").collect();
+    assert_eq!(languages, vec!["pt"]);
+    assert_eq!(entries[0].title["pt"], "Universidade de Teste");
+    assert!(warnings.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_acronym_pick() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[{
+        "id": "https://ror.org/00aaa1234",
+        "name": "International Astronomical Union",
+        "acronyms": ["INTERNATIONAL", "IAU"]
+    }]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    for (pick, expected) in [("first", "INTERNATIONAL"), ("shortest", "IAU"), ("longest", "INTERNATIONAL")] {
+        let options = affiliations::ConvertOptions { acronym_pick: pick.parse()?, ..Default::default() };
+        let entries = affiliations::convert_items(&items, &options)?;
+        assert_eq!(entries[0].acronym.as_deref(), Some(expected), "--acronym-pick {}", pick);
+    }
+
+    let pick: affiliations::AcronymPick = "longest".parse()?;
+    assert_eq!(pick.pick(["ABC".to_string(), "XYZ".to_string()]), Some("ABC".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_json_lines_output() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University", "acronyms": ["TU"] },
+        { "id": "https://ror.org/00bbb5678", "name": "Other University" },
+        { "id": "https://ror.org/00ccc9012", "name": "Third Institute" }
+    ]"#;
+    let options = affiliations::ConvertOptions {
+        output_format: "jsonl".parse()?,
+        vocab_version: Some("v1".to_string()),
+        ..Default::default()
+    };
+
+    let mut output = Vec::new();
+    let report = affiliations::convert_json_to_writer(input.as_bytes(), &mut output, &options)?;
+    let output = String::from_utf8(output)?;
+    assert!(!output.starts_with('\u{feff}'));
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), report.emitted_total);
+    let entries = lines
+        .iter()
+        .map(|line| serde_json::from_str::<affiliations::YamlEntry>(line))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(entries[0].id, "00aaa1234");
+    assert_eq!(entries[0].acronym.as_deref(), Some("TU"));
+    assert_eq!(entries[2].name, "Third Institute");
+
+    Ok(())
+}
+
+#[test]
+fn test_record_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Technische Universitat Berlin", "country_code": "DE" },
+        { "id": "https://ror.org/00bbb5678", "name": "Max Planck Society", "country_code": "DE" },
+        { "id": "https://ror.org/00ccc9012", "name": "Universite de Test", "country_code": "FR" }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+
+    let options = affiliations::ConvertOptions {
+        record_filter: Some(r#"country == "DE" && name contains "Universit""#.parse()?),
+        verbosity: Verbosity::Quiet,
+        ..Default::default()
+    };
+    let (entries, warnings) = affiliations::convert_items_with_warnings(&items, &options)?;
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, vec!["00aaa1234"]);
+    assert!(warnings.iter().all(|warning| warning.kind == WarningKind::SkippedRecord));
+
+    let filter: affiliations::RecordFilter = r#"!(country == "DE") || (name starts_with "Max" && id != "x")"#.parse()?;
+    let kept: Vec<bool> = items.iter().map(|item| filter.matches(item)).collect();
+    assert_eq!(kept, vec![false, true, true]);
+
+    let err = "city == \"Berlin\"".parse::<affiliations::RecordFilter>().unwrap_err();
+    assert!(err.contains("unknown field 'city'"), "{}", err);
+    assert!("name ==".parse::<affiliations::RecordFilter>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_overrides_replace_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let overrides_path = temp_dir.path().join("overrides.json");
+    fs::write(&overrides_path, r#"{ "https://ror.org/00aaa1234": { "name": "Test University of Applied Sciences", "country": "de" } }"#)?;
+
+    let input = r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test Univ.", "country_code": "AT", "acronyms": ["TU"] },
+        { "id": "https://ror.org/00bbb5678", "name": "Other University", "country_code": "FR" }
+    ]"#;
+    let items = affiliations::read_items_from(input.as_bytes(), &Default::default())?;
+    let options = affiliations::ConvertOptions {
+        overrides: affiliations::read_overrides(&overrides_path)?,
+        ..Default::default()
+    };
+    let entries = affiliations::convert_items(&items, &options)?;
+
+    assert_eq!(entries[0].name, "Test University of Applied Sciences");
+    assert_eq!(entries[0].country.as_deref(), Some("DE"));
+    assert_eq!(entries[0].acronym.as_deref(), Some("TU"));
+    assert_eq!(entries[0].title["en"], "Test Univ.");
+    assert_eq!(entries[1], affiliations::convert_items(&items, &Default::default())?[1]);
+
+    fs::write(&overrides_path, r#"{ "00aaa1234": { "nmae": "Typo" } }"#)?;
+    assert!(affiliations::read_overrides(&overrides_path).is_err());
+
+    Ok(())
+}
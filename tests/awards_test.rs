@@ -0,0 +1,42 @@
+use invenio_vocb_converter::vocab::affiliations::ConvertOptions;
+use invenio_vocb_converter::vocab::awards;
+use std::fs::File;
+use std::io::Write;
+use tempfile::tempdir;
+
+#[test]
+fn test_award_description_is_truncated() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("awards.json");
+    let mut json_file = File::create(&json_path)?;
+    let long_abstract = "Climate models at regional scale. ".repeat(20);
+    write!(json_file, r#"[
+        {{
+            "number": "101000001",
+            "title": "Regional Climate Modelling",
+            "acronym": "RECLIM",
+            "funder": {{ "id": "https://ror.org/00k4n6c32", "name": "European Commission" }},
+            "abstract": "{}"
+        }},
+        {{ "id": "00k4n6c32::101000002", "title": "No Abstract" }}
+    ]"#, long_abstract)?;
+    json_file.flush()?;
+
+    let options = ConvertOptions { max_description_length: Some(40), ..ConvertOptions::default() };
+    let entries = awards::convert_json_to_entries(&json_path, &options)?;
+    assert_eq!(entries.len(), 2);
+
+    let award = &entries[0];
+    assert_eq!(award.id, "00k4n6c32::101000001");
+    assert_eq!(award.funder.as_ref().map(|funder| funder.id.as_str()), Some("00k4n6c32"));
+    assert_eq!(award.description.as_deref(), Some("Climate models at regional scale. Climat"));
+    assert_eq!(entries[1].description, None);
+
+    let uncapped = awards::convert_json_to_entries(&json_path, &ConvertOptions::default())?;
+    assert_eq!(uncapped[0].description.as_deref(), Some(long_abstract.trim_end()));
+
+    let yaml = serde_yaml::to_string(&entries)?;
+    assert!(yaml.contains("description: Climate models at regional scale. Climat\n"));
+
+    Ok(())
+}
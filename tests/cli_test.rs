@@ -0,0 +1,657 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::process::Command;
+use tempfile::tempdir;
+
+const BIN: &str = env!("CARGO_BIN_EXE_invenio-vocb-converter");
+
+#[test]
+fn test_preview_prints_entries_without_writing() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("preview.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "First Institute" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Second Institute" }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("preview.yaml");
+    let output = Command::new(BIN)
+        .args(["--preview", "1", "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let entries: Vec<serde_yaml::Value> = serde_yaml::from_str(&stdout)?;
+    assert_eq!(entries.len(), 1);
+    assert!(stdout.contains("First Institute"));
+    assert!(!stdout.contains("Second Institute"));
+    assert!(!yaml_path.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_skip_if_unchanged() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[{{ "id": "https://ror.org/00aaa1234", "name": "Test" }}]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let run = || {
+        Command::new(BIN)
+            .args(["--skip-if-unchanged", "affiliations"])
+            .arg(&json_path)
+            .arg(&yaml_path)
+            .output()
+    };
+
+    let first = run()?;
+    assert!(first.status.success());
+    assert!(!String::from_utf8(first.stdout)?.contains("skipping"));
+    assert!(yaml_path.exists());
+
+    let second = run()?;
+    assert!(second.status.success());
+    assert!(String::from_utf8(second.stdout)?.contains("skipping conversion"));
+
+    Ok(())
+}
+
+#[test]
+fn test_quiet_and_verbose_levels() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": null }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let run = |level: &str| {
+        Command::new(BIN)
+            .args([level, "--overwrite", "--on-empty-name", "skip", "affiliations"])
+            .arg(&json_path)
+            .arg(&yaml_path)
+            .output()
+    };
+
+    let quiet = run("-q")?;
+    assert!(quiet.status.success());
+    assert_eq!(String::from_utf8(quiet.stderr)?, "");
+
+    let verbose = run("-v")?;
+    assert!(verbose.status.success());
+    let stderr = String::from_utf8(verbose.stderr)?;
+    assert!(stderr.contains("Converted 1 of 2 records (1 skipped)"));
+    assert!(stderr.contains("Skipping record 1 (id 'https://ror.org/00bbb5678'): empty name"));
+
+    Ok(())
+}
+
+#[test]
+fn test_metrics_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{ "id": "https://ror.org/00aaa1234", "name": "Test" }},
+        {{ "id": "https://ror.org/00bbb5678", "name": "Other" }},
+        {{ "id": "https://ror.org/00ccc9012", "name": null }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let metrics_path = temp_dir.path().join("converter.prom");
+    let output = Command::new(BIN)
+        .args(["--on-empty-name", "skip", "--metrics-file"])
+        .arg(&metrics_path)
+        .arg("affiliations")
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let metrics = fs::read_to_string(&metrics_path)?;
+    assert!(metrics.contains("# TYPE invenio_vocab_records_total counter"));
+    assert!(metrics.contains("invenio_vocab_records_total{vocab=\"affiliations\"} 3\n"));
+    assert!(metrics.contains("invenio_vocab_emitted_total{vocab=\"affiliations\"} 2\n"));
+    assert!(metrics.contains("invenio_vocab_skipped_total{vocab=\"affiliations\"} 1\n"));
+
+    let duration_line = metrics
+        .lines()
+        .find(|line| line.starts_with("invenio_vocab_duration_seconds{"))
+        .expect("duration metric");
+    let duration: f64 = duration_line.rsplit(' ').next().unwrap().parse()?;
+    assert!((0.0..60.0).contains(&duration));
+
+    Ok(())
+}
+
+#[test]
+fn test_exit_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let yaml_path = temp_dir.path().join("output.yaml");
+
+    let valid_path = temp_dir.path().join("valid.json");
+    fs::write(&valid_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": null }]"#)?;
+    let broken_path = temp_dir.path().join("broken.json");
+    fs::write(&broken_path, r#"[{ "id": "#)?;
+    let missing_path = temp_dir.path().join("missing.json");
+
+    let exit_code = |args: &[&str], input: &std::path::Path| -> std::io::Result<Option<i32>> {
+        let output = Command::new(BIN).args(args).arg(input).arg(&yaml_path).output()?;
+        Ok(output.status.code())
+    };
+
+    // Usage error: wrong number of arguments.
+    let usage = Command::new(BIN).arg("affiliations").output()?;
+    assert_eq!(usage.status.code(), Some(2));
+
+    assert_eq!(exit_code(&["funding"], &valid_path)?, Some(3));
+    assert_eq!(exit_code(&["unknown"], &valid_path)?, Some(3));
+    let baseline = valid_path.to_str().ok_or("non-UTF-8 path")?;
+    assert_eq!(exit_code(&["--baseline", baseline, "names"], &valid_path)?, Some(3));
+    let stats_path = temp_dir.path().join("stats.tsv");
+    let stats = stats_path.to_str().ok_or("non-UTF-8 path")?;
+    assert_eq!(exit_code(&["--lang-stats", stats, "subjects"], &valid_path)?, Some(3));
+    assert!(!stats_path.exists());
+    let report_path = temp_dir.path().join("duplicates.tsv");
+    let report = report_path.to_str().ok_or("non-UTF-8 path")?;
+    assert_eq!(exit_code(&["--report-duplicates", report, "affiliations"], &valid_path)?, Some(3));
+    assert!(!report_path.exists());
+    assert_eq!(exit_code(&["affiliations"], &missing_path)?, Some(4));
+    assert_eq!(exit_code(&["affiliations"], &broken_path)?, Some(5));
+    assert_eq!(exit_code(&["--on-empty-name", "error", "affiliations"], &valid_path)?, Some(6));
+    assert_eq!(exit_code(&["affiliations"], &valid_path)?, Some(0));
+
+    Ok(())
+}
+
+#[test]
+fn test_existing_output_is_not_overwritten_without_tty() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#)?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let existing = "- id: 00zzz0000\n  name: Previous\n";
+    fs::write(&yaml_path, existing)?;
+
+    let refused = Command::new(BIN)
+        .arg("affiliations")
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    assert_eq!(refused.status.code(), Some(4));
+    let stderr = String::from_utf8(refused.stderr)?;
+    assert!(stderr.contains("pass --overwrite"));
+    assert!(!stderr.contains("[y/N]"));
+    assert_eq!(fs::read_to_string(&yaml_path)?, existing);
+
+    let forced = Command::new(BIN)
+        .args(["--overwrite", "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    assert!(forced.status.success());
+    assert!(fs::read_to_string(&yaml_path)?.contains("Test"));
+
+    Ok(())
+}
+
+#[test]
+fn test_fixtures_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#)?;
+
+    let fixtures = temp_dir.path().join("app_data");
+    fs::create_dir(&fixtures)?;
+    fs::write(fixtures.join("vocabularies.yaml"), "languages:\n  pid-type: lng\n  data-file: vocabularies/languages.yaml\n")?;
+
+    let output = Command::new(BIN)
+        .arg("--fixtures-dir")
+        .arg(&fixtures)
+        .arg("affiliations")
+        .arg(&json_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let data = fs::read_to_string(fixtures.join("vocabularies").join("affiliations.yaml"))?;
+    assert!(data.contains("id: 00aaa1234"));
+
+    let index: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(fixtures.join("vocabularies.yaml"))?)?;
+    assert_eq!(index["affiliations"]["data-file"].as_str(), Some("vocabularies/affiliations.yaml"));
+    assert_eq!(index["affiliations"]["pid-type"].as_str(), Some("aff"));
+    assert_eq!(index["languages"]["pid-type"].as_str(), Some("lng"));
+
+    Ok(())
+}
+
+#[test]
+fn test_profile_reports_stages() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#)?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let output = Command::new(BIN)
+        .args(["-q", "--profile", "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr)?;
+    for stage in ["read", "parse", "transform", "write", "total"] {
+        assert!(stderr.lines().any(|line| line.trim_start().starts_with(stage)), "missing stage {}", stage);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_profile_read_stage_reads_and_decompresses_input() -> Result<(), Box<dyn std::error::Error>> {
+    use flate2::write::GzEncoder;
+
+    let temp_dir = tempdir()?;
+    let records: Vec<String> = (0..50_000)
+        .map(|i| format!(r#"{{ "id": "https://ror.org/{:09}", "name": "Institute number {}" }}"#, i, i))
+        .collect();
+    let json_path = temp_dir.path().join("input.json.gz");
+    let mut encoder = GzEncoder::new(File::create(&json_path)?, flate2::Compression::default());
+    write!(encoder, "[{}]", records.join(","))?;
+    encoder.finish()?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let output = Command::new(BIN)
+        .args(["-q", "--profile", "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+
+    // Decompressing a few megabytes takes well over a millisecond; opening the file does not.
+    let stderr = String::from_utf8(output.stderr)?;
+    let read_ms: f64 = stderr
+        .lines()
+        .find_map(|line| line.trim_start().strip_prefix("read"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or("missing read stage")?
+        .parse()?;
+    assert!(read_ms >= 1.0, "{}", stderr);
+
+    Ok(())
+}
+
+#[test]
+fn test_manifest_run() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    fs::write(
+        temp_dir.path().join("ror.json"),
+        r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test", "acronyms": ["T", "TU"] }]"#,
+    )?;
+    fs::write(
+        temp_dir.path().join("orcid.json"),
+        r#"[{ "id": "0000-0002-1825-0097", "given_name": "Josiah", "family_name": "Carberry" }]"#,
+    )?;
+    let manifest_path = temp_dir.path().join("bootstrap.toml");
+    fs::write(&manifest_path, r#"
+# Bootstrap vocabularies
+[[vocabulary]]
+type = "affiliations"
+input = "ror.json"
+output = "affiliations.yaml"
+options = ["--all-acronyms", "--lang-allowlist", "en,fr"]
+
+[[vocabulary]]
+type = "names"
+input = "orcid.json"
+output = "names.yaml"
+"#)?;
+
+    let output = Command::new(BIN).arg("--manifest-run").arg(&manifest_path).output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let affiliations = fs::read_to_string(temp_dir.path().join("affiliations.yaml"))?;
+    assert!(affiliations.contains("acronyms:"));
+    let names = fs::read_to_string(temp_dir.path().join("names.yaml"))?;
+    assert!(names.contains("name: \"Carberry, Josiah\""));
+
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("converted 2 of 2 vocabularies (2 of 2 records emitted)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_manifest_accepts_full_toml_syntax() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    fs::write(temp_dir.path().join("ror.json"), r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test", "acronyms": ["T", "TU"] }]"#)?;
+    let manifest_path = temp_dir.path().join("bootstrap.toml");
+    fs::write(&manifest_path, r#"
+[[vocabulary]]
+type = 'affiliations'
+input = 'ror.json'
+output = "affiliations.yaml" # written next to the manifest
+options = [
+    "--all-acronyms",
+    "--tag", "bootstrap",
+]
+"#)?;
+
+    let output = Command::new(BIN).arg("--manifest-run").arg(&manifest_path).output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let affiliations = fs::read_to_string(temp_dir.path().join("affiliations.yaml"))?;
+    assert!(affiliations.contains("acronyms:"));
+    assert!(affiliations.contains("bootstrap"));
+
+    // Unknown keys are rejected rather than silently ignored.
+    fs::write(&manifest_path, "[[vocabulary]]\ntype = \"affiliations\"\ninput = \"ror.json\"\noutput = \"a.yaml\"\noptons = []\n")?;
+    let output = Command::new(BIN).arg("--manifest-run").arg(&manifest_path).output()?;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8(output.stderr)?.contains("optons"));
+
+    Ok(())
+}
+
+#[test]
+fn test_manifest_continue_after_failure() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    fs::write(temp_dir.path().join("broken.json"), r#"[{ "id": "#)?;
+    fs::write(temp_dir.path().join("good.json"), r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test" }]"#)?;
+    let manifest_path = temp_dir.path().join("batch.toml");
+    fs::write(&manifest_path, r#"
+[[vocabulary]]
+type = "affiliations"
+input = "broken.json"
+output = "broken.yaml"
+
+[[vocabulary]]
+type = "affiliations"
+input = "good.json"
+output = "good.yaml"
+"#)?;
+    let good_output = temp_dir.path().join("good.yaml");
+
+    let fail_fast = Command::new(BIN).args(["--fail-fast", "--manifest-run"]).arg(&manifest_path).output()?;
+    assert_eq!(fail_fast.status.code(), Some(5));
+    assert!(!good_output.exists());
+
+    let continued = Command::new(BIN).args(["--continue", "--manifest-run"]).arg(&manifest_path).output()?;
+    assert_eq!(continued.status.code(), Some(5));
+    assert!(fs::read_to_string(&good_output)?.contains("id: 00aaa1234"));
+    let stderr = String::from_utf8(continued.stderr)?;
+    assert!(stderr.contains("converted 1 of 2 vocabularies"));
+    assert!(stderr.contains("1 of 2 conversions failed"));
+
+    Ok(())
+}
+
+#[test]
+fn test_trace_record_logs_steps() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(
+        &json_path,
+        r#"[
+            { "id": "https://ror.org/00aaa1234", "name": "Tëst University", "acronyms": ["TU"],
+              "labels": [{ "iso639": "fr", "label": "Université Test" }] },
+            { "id": "https://ror.org/00bbb5678", "name": "Other University" }
+        ]"#,
+    )?;
+
+    let yaml_path = temp_dir.path().join("output.yaml");
+    let output = Command::new(BIN)
+        .args(["--trace-record", "00aaa1234", "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr)?;
+    let trace: Vec<&str> = stderr.lines().filter(|line| line.starts_with("trace ")).collect();
+    assert!(trace.iter().all(|line| line.starts_with("trace 00aaa1234: ")));
+    for step in [
+        "id 'https://ror.org/00aaa1234' -> '00aaa1234'",
+        "name 'Tëst University' -> 'Test University'",
+        "label fr 'Université Test' -> 'Universite Test'",
+        "acronym 'TU' selected",
+        "identifier ror '00aaa1234'",
+    ] {
+        assert!(trace.iter().any(|line| line.contains(step)), "missing step {}", step);
+    }
+    assert!(!stderr.contains("00bbb5678"));
+
+    Ok(())
+}
+
+#[test]
+fn test_create_dirs_for_nested_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test University" }]"#)?;
+    let yaml_path = temp_dir.path().join("vocabularies").join("2024").join("affiliations.yaml");
+
+    let output = Command::new(BIN).arg("affiliations").arg(&json_path).arg(&yaml_path).output()?;
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("does not exist; pass --create-dirs to create it"), "{}", stderr);
+    assert!(!yaml_path.exists());
+
+    let output = Command::new(BIN).args(["--create-dirs", "affiliations"]).arg(&json_path).arg(&yaml_path).output()?;
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&yaml_path)?.contains("Test University"));
+
+    Ok(())
+}
+
+#[test]
+fn test_tee_writes_identical_copy() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University", "acronyms": ["TU"] },
+        { "id": "https://ror.org/00bbb5678", "name": "Other University" }
+    ]"#)?;
+    let yaml_path = temp_dir.path().join("affiliations.yaml");
+    let archive_path = temp_dir.path().join("archive.yaml");
+
+    let output = Command::new(BIN)
+        .args(["--vocab-version", "v1", "--tee"])
+        .arg(&archive_path)
+        .arg("affiliations")
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let written = fs::read(&yaml_path)?;
+    assert!(String::from_utf8(written.clone())?.contains("Other University"));
+    assert_eq!(fs::read(&archive_path)?, written);
+
+    // No copy is created when the primary output cannot be.
+    let stray_path = temp_dir.path().join("stray.yaml");
+    let output = Command::new(BIN)
+        .arg("--tee")
+        .arg(&stray_path)
+        .arg("affiliations")
+        .arg(&json_path)
+        .arg(temp_dir.path().join("missing").join("affiliations.yaml"))
+        .output()?;
+    assert_eq!(output.status.code(), Some(4));
+    assert!(!stray_path.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_describes_pipeline_without_converting() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[{ "id": "https://ror.org/00aaa1234", "name": "Test University" }]"#)?;
+    let yaml_path = temp_dir.path().join("affiliations.yaml");
+
+    let output = Command::new(BIN)
+        .args(["--explain", "--lang-allowlist", "de,fr", "--on-empty-name", "skip", "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Filters:\n"), "{}", stdout);
+    assert!(stdout.contains("  - keep only titles in en, de, fr\n"), "{}", stdout);
+    assert!(stdout.contains("  - skip records with an empty name\n"), "{}", stdout);
+    assert!(stdout.contains("emit identifiers with scheme 'ror'"), "{}", stdout);
+    assert!(!stdout.contains("gzip"), "{}", stdout);
+    assert!(!yaml_path.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_mentions_gzip_only_for_gzip_input() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let input_path = temp_dir.path().join("affiliations.csv.gz");
+    fs::write(&input_path, include_bytes!("fixtures/affiliations.csv.gz"))?;
+    let yaml_path = temp_dir.path().join("affiliations.yaml");
+
+    let output = Command::new(BIN).args(["--explain", "affiliations"]).arg(&input_path).arg(&yaml_path).output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("  - decompress gzip input transparently\n"), "{}", stdout);
+
+    Ok(())
+}
+
+#[test]
+fn test_count_prints_record_count() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("input.json");
+    fs::write(&json_path, r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University", "country_code": "DE" },
+        { "id": "https://ror.org/00bbb5678", "name": "Other University", "country_code": "DE" },
+        { "id": "https://ror.org/00ccc9012", "name": "Third Institute", "country_code": "FR" }
+    ]"#)?;
+    let yaml_path = temp_dir.path().join("affiliations.yaml");
+
+    let output = Command::new(BIN).args(["--count", "affiliations"]).arg(&json_path).arg(&yaml_path).output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "3\n");
+    assert!(!yaml_path.exists());
+
+    let output = Command::new(BIN)
+        .args(["--count", "--record-filter", r#"country == "DE""#, "affiliations"])
+        .arg(&json_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert_eq!(String::from_utf8(output.stdout)?, "2\n");
+    assert!(!yaml_path.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_count_excludes_skipped_and_merged_records() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let yaml_path = temp_dir.path().join("output.yaml");
+
+    let affiliations_path = temp_dir.path().join("affiliations.json");
+    fs::write(&affiliations_path, r#"[
+        { "id": "https://ror.org/00aaa1234", "name": "Test University" },
+        { "id": "https://ror.org/00bbb5678", "name": "" }
+    ]"#)?;
+    let output = Command::new(BIN)
+        .args(["--count", "--on-empty-name", "skip", "affiliations"])
+        .arg(&affiliations_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "1\n");
+
+    // A JSON array is streamed; the empty name aborts the count just as it aborts the conversion.
+    let output = Command::new(BIN)
+        .args(["--count", "--input-format", "json", "--on-empty-name", "error", "affiliations"])
+        .arg(&affiliations_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert_eq!(output.status.code(), Some(6));
+
+    let output = Command::new(BIN)
+        .args(["--count", "--baseline"])
+        .arg(&affiliations_path)
+        .arg("affiliations")
+        .arg(&affiliations_path)
+        .arg(&yaml_path)
+        .output()?;
+    assert_eq!(output.status.code(), Some(3));
+
+    let names_path = temp_dir.path().join("names.json");
+    fs::write(&names_path, r#"[
+        { "id": "https://orcid.org/0000-0001-2345-6789", "given_name": "Ada", "family_name": "Lovelace" },
+        { "id": "https://orcid.org/0000-0001-2345-6789", "given_name": "Ada", "family_name": "Lovelace" },
+        { "id": "https://orcid.org/0000-0002-1825-0097", "given_name": "Alan", "family_name": "Turing" }
+    ]"#)?;
+    let output = Command::new(BIN).args(["--count", "names"]).arg(&names_path).arg(&yaml_path).output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "2\n");
+    assert!(!yaml_path.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_changelog_between_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let old_path = temp_dir.path().join("old.yaml");
+    let new_path = temp_dir.path().join("new.yaml");
+    fs::write(&old_path, "\u{feff}---\n- id: 00aaa1234\n  name: Test Univ\n- id: 00bbb5678\n  name: Closed Institute\n")?;
+    fs::write(&new_path, "---\n- id: 00aaa1234\n  name: Test University\n- id: 00ccc9012\n  name: New Institute\n")?;
+
+    let output = Command::new(BIN).arg("changelog").arg(&old_path).arg(&new_path).output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("## Added (1)\n\n- `00ccc9012` New Institute\n"), "{}", stdout);
+    assert!(stdout.contains("## Removed (1)\n\n- `00bbb5678` Closed Institute\n"), "{}", stdout);
+    assert!(stdout.contains("## Renamed (1)\n\n- `00aaa1234` Test Univ -> Test University\n"), "{}", stdout);
+
+    Ok(())
+}
+
+#[test]
+fn test_truncated_gzip_input_is_parse_error() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let compressed = include_bytes!("fixtures/affiliations.csv.gz");
+    let input_path = temp_dir.path().join("truncated.csv.gz");
+    fs::write(&input_path, &compressed[..compressed.len() / 2])?;
+    let yaml_path = temp_dir.path().join("affiliations.yaml");
+
+    let output = Command::new(BIN).args(["--input-format", "csv", "affiliations"]).arg(&input_path).arg(&yaml_path).output()?;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8(output.stderr)?.contains("Invalid gzip stream"));
+
+    Ok(())
+}
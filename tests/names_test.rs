@@ -0,0 +1,172 @@
+use invenio_vocb_converter::vocab::names;
+use std::fs::{self, File};
+use std::io::Write;
+use tempfile::tempdir;
+
+#[test]
+fn test_dedupe_by_orcid() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("names.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{
+            "id": "https://orcid.org/0000-0001-8135-3489",
+            "given_name": "Lars Holm",
+            "family_name": "Nielsen",
+            "affiliations": [{{ "name": "CERN" }}]
+        }},
+        {{
+            "id": "https://orcid.org/0000-0002-1825-0097",
+            "given_name": "Josiah",
+            "family_name": "Carberry",
+            "affiliations": [{{ "name": "Brown University" }}]
+        }},
+        {{
+            "id": "0000-0001-8135-3489",
+            "given_name": "Lars Holm",
+            "family_name": "Nielsen",
+            "affiliations": [{{ "name": "CERN" }}, {{ "name": "Zenodo" }}]
+        }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let entries = names::convert_json_to_entries(&json_path, &Default::default())?;
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, "0000-0001-8135-3489");
+    assert_eq!(entries[0].name, "Nielsen, Lars Holm");
+    assert_eq!(entries[0].identifiers[0].identifier, "0000-0001-8135-3489");
+    assert_eq!(entries[0].identifiers[0].scheme, "orcid");
+    let affiliations: Vec<&str> = entries[0].affiliations.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(affiliations, vec!["CERN", "Zenodo"]);
+    assert_eq!(entries[1].id, "0000-0002-1825-0097");
+
+    Ok(())
+}
+
+#[test]
+fn test_report_duplicates() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("names.json");
+    fs::write(&json_path, r#"[
+        { "id": "0000-0001-8135-3489", "given_name": "Lars", "family_name": "Nielsen" },
+        { "id": "0000-0002-1825-0097", "given_name": "Josiah", "family_name": "Carberry" },
+        { "id": "https://orcid.org/0000-0001-8135-3489", "given_name": "Lars", "family_name": "Nielsen" },
+        { "id": "0000-0002-1825-0097", "given_name": "Josiah", "family_name": "Carberry" },
+        { "id": "0000-0001-8135-3489", "given_name": "Lars", "family_name": "Nielsen" }
+    ]"#)?;
+
+    let items = names::read_items(&json_path, &Default::default())?;
+    let (entries, duplicates) = names::convert_items_with_duplicates(&items, &Default::default())?;
+    assert_eq!(entries.len(), 2);
+
+    let report_path = temp_dir.path().join("duplicates.tsv");
+    names::write_duplicates_report(&report_path, &duplicates)?;
+    let report = fs::read_to_string(&report_path)?;
+    assert_eq!(
+        report,
+        "id\tkept\tdropped\n0000-0001-8135-3489\t0\t2,4\n0000-0002-1825-0097\t1\t3\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_affiliations_with_resolved_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let item: names::NameItem = serde_json::from_str(
+        r#"{
+            "id": "https://orcid.org/0000-0002-1825-0097",
+            "given_name": "Josiah",
+            "family_name": "Carberry",
+            "affiliations": [
+                { "id": "https://ror.org/05gq02987", "name": "Brown Universität" },
+                { "name": "Wesleyan University" }
+            ]
+        }"#,
+    )?;
+
+    let entry = names::convert_item(&item, &Default::default());
+    let yaml = serde_yaml::to_string(&entry.affiliations)?;
+
+    assert_eq!(entry.affiliations[0].id.as_deref(), Some("05gq02987"));
+    assert_eq!(entry.affiliations[0].name, "Brown Universitat");
+    assert_eq!(entry.affiliations[1].id, None);
+    assert_eq!(entry.affiliations[1].name, "Wesleyan University");
+    assert!(yaml.contains("- id: 05gq02987\n  name: Brown Universitat\n- name: Wesleyan University\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_redact_given_names() -> Result<(), Box<dyn std::error::Error>> {
+    let item: names::NameItem = serde_json::from_str(
+        r#"{
+            "id": "https://orcid.org/0000-0001-8135-3489",
+            "given_name": "Lars Holm",
+            "family_name": "Nielsen"
+        }"#,
+    )?;
+    let options = invenio_vocb_converter::vocab::affiliations::ConvertOptions {
+        redact: vec!["given_name".parse()?],
+        ..Default::default()
+    };
+
+    let entry = names::convert_item(&item, &options);
+
+    assert_eq!(entry.given_name, names::REDACTED);
+    assert_eq!(entry.family_name, "Nielsen");
+    assert_eq!(entry.name, "Nielsen, [REDACTED]");
+    assert_eq!(entry.id, "0000-0001-8135-3489");
+    assert_eq!(entry.identifiers[0].identifier, "0000-0001-8135-3489");
+    assert_eq!(entry.identifiers[0].scheme, "orcid");
+
+    Ok(())
+}
+
+#[test]
+fn test_name_format() -> Result<(), Box<dyn std::error::Error>> {
+    let item: names::NameItem = serde_json::from_str(
+        r#"{
+            "id": "https://orcid.org/0000-0001-8135-3489",
+            "given_name": "Lars Holm",
+            "family_name": "Nielsen"
+        }"#,
+    )?;
+
+    let options = invenio_vocb_converter::vocab::affiliations::ConvertOptions {
+        name_format: "family-comma-given".parse()?,
+        ..Default::default()
+    };
+    assert_eq!(names::convert_item(&item, &options).name, "Nielsen, Lars Holm");
+
+    let options = invenio_vocb_converter::vocab::affiliations::ConvertOptions {
+        name_format: "given-family".parse()?,
+        ..Default::default()
+    };
+    let entry = names::convert_item(&item, &options);
+    assert_eq!(entry.name, "Lars Holm Nielsen");
+    assert_eq!(entry.given_name, "Lars Holm");
+    assert_eq!(entry.family_name, "Nielsen");
+
+    Ok(())
+}
+
+#[test]
+fn test_gzip_input() -> Result<(), Box<dyn std::error::Error>> {
+    use flate2::write::GzEncoder;
+
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("names.json.gz");
+    let mut encoder = GzEncoder::new(File::create(&json_path)?, flate2::Compression::default());
+    write!(encoder, r#"[
+        {{ "id": "https://orcid.org/0000-0002-1825-0097", "given_name": "Josiah", "family_name": "Carberry" }}
+    ]"#)?;
+    encoder.finish()?;
+
+    let entries = names::convert_json_to_entries(&json_path, &Default::default())?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "0000-0002-1825-0097");
+    assert_eq!(entries[0].name, "Carberry, Josiah");
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use invenio_vocb_converter::vocab::affiliations::ConvertOptions;
+use invenio_vocb_converter::vocab::subjects;
+use std::fs::File;
+use std::io::Write;
+use tempfile::tempdir;
+
+#[test]
+fn test_convert_subjects() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("subjects.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"[
+        {{
+            "id": "https://id.nlm.nih.gov/mesh/D000001",
+            "subject": "Calcimycin",
+            "labels": [{{ "iso639": "fr", "label": "Calcimycine" }}],
+            "broader": ["https://id.nlm.nih.gov/mesh/D001583"]
+        }}
+    ]"#)?;
+    json_file.flush()?;
+
+    let items = subjects::read_items(&json_path, &ConvertOptions::default())?;
+    assert_eq!(items.len(), 1);
+
+    let entry = subjects::convert_item(&items[0], &ConvertOptions::default());
+    assert_eq!(entry.id, "https://id.nlm.nih.gov/mesh/D000001");
+    assert_eq!(entry.scheme, "mesh");
+    assert_eq!(entry.subject, "Calcimycin");
+
+    let skos = subjects::convert_item_skos(&items[0], &ConvertOptions::default());
+    assert_eq!(skos.pref_label.get("en"), Some(&"Calcimycin".to_string()));
+    assert_eq!(skos.pref_label.get("fr"), Some(&"Calcimycine".to_string()));
+    assert_eq!(skos.broader, vec!["https://id.nlm.nih.gov/mesh/D001583"]);
+
+    let yaml = serde_yaml::to_string(&vec![skos])?;
+    assert!(yaml.contains("prefLabel:"));
+    assert!(yaml.contains("inScheme: mesh"));
+
+    Ok(())
+}
+
+#[test]
+fn test_subjects_records_path() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let json_path = temp_dir.path().join("nested.json");
+    let mut json_file = File::create(&json_path)?;
+    write!(json_file, r#"{{
+        "response": {{
+            "concepts": [
+                {{
+                    "items": [
+                        {{ "id": "https://id.nlm.nih.gov/mesh/D000001", "subject": "Calcimycin" }},
+                        {{ "id": "https://id.nlm.nih.gov/mesh/D000002", "subject": "Temefos" }}
+                    ]
+                }}
+            ]
+        }}
+    }}"#)?;
+    json_file.flush()?;
+
+    let options = ConvertOptions {
+        records_path: Some("response.concepts.0.items".to_string()),
+        ..Default::default()
+    };
+    let items = subjects::read_items(&json_path, &options)?;
+    let subjects: Vec<_> = items.iter().map(|item| subjects::convert_item(item, &options).subject).collect();
+    assert_eq!(subjects, ["Calcimycin", "Temefos"]);
+
+    Ok(())
+}